@@ -46,7 +46,7 @@ where
             let mut f = File::open(path)?;
 
             f.read_to_end(&mut buffer)?;
-            zip_writer.write_all(&*buffer)?;
+            zip_writer.write_all(&buffer)?;
             buffer.clear();
         } else if !name.as_os_str().is_empty() {
             println!("build-resources={:?}={:?}", path, name);
@@ -67,9 +67,9 @@ fn make_zip(
     }
 
     let path = Path::new(dst_file);
-    fs::remove_file(&path).ok();
+    fs::remove_file(path).ok();
 
-    let file = File::create(&path).unwrap();
+    let file = File::create(path).unwrap();
 
     let walkdir = WalkDir::new(src_dir);
     let it = walkdir.into_iter();
@@ -0,0 +1,77 @@
+use std::io::{Cursor, Read};
+use std::sync::Arc;
+
+use ggez::{Context, GameError, GameResult};
+use log::warn;
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source, SpatialSink};
+
+/// Open the default output device. Without one the game runs silently.
+pub fn open() -> Option<(OutputStream, OutputStreamHandle)> {
+    match OutputStream::try_default() {
+        Ok(output) => Some(output),
+        Err(e) => {
+            warn!("Can't open audio device, sound is off: {}", e);
+            None
+        }
+    }
+}
+
+/// Contents of a sound file, decoded every time it's played.
+#[derive(Clone)]
+pub struct Clip(Arc<[u8]>);
+
+impl AsRef<[u8]> for Clip {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Clip {
+    fn read(ctx: &Context, path: &str) -> GameResult<Clip> {
+        let mut data = Vec::new();
+        ctx.fs.open(path)?.read_to_end(&mut data)?;
+        let clip = Clip(data.into());
+        // Broken files are turned away here rather than on every play
+        clip.decoder()?;
+        Ok(clip)
+    }
+
+    /// Load a sound file, logging why when it can't be played.
+    pub fn load(ctx: &Context, path: &str) -> Option<Clip> {
+        match Clip::read(ctx, path) {
+            Ok(clip) => Some(clip),
+            Err(e) => {
+                warn!("Can't load sound {}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    fn decoder(&self) -> GameResult<Decoder<Cursor<Clip>>> {
+        Decoder::new(Cursor::new(self.clone())).map_err(|e| GameError::AudioError(e.to_string()))
+    }
+
+    /// Play once at `pan` between the left (-1) and right (1) ear.
+    pub fn play(
+        &self,
+        output: &OutputStreamHandle,
+        pan: f32,
+        pitch: f32,
+        volume: f32,
+    ) -> GameResult {
+        let sink = SpatialSink::try_new(output, [pan, 0.0, 0.0], [-1.0, 0.0, 0.0], [1.0, 0.0, 0.0])
+            .map_err(|e| GameError::AudioError(e.to_string()))?;
+        sink.set_volume(volume);
+        sink.append(self.decoder()?.speed(pitch));
+        sink.detach();
+        Ok(())
+    }
+
+    /// Play over and over until the returned sink is dropped.
+    pub fn play_loop(&self, output: &OutputStreamHandle, volume: f32) -> GameResult<Sink> {
+        let sink = Sink::try_new(output).map_err(|e| GameError::AudioError(e.to_string()))?;
+        sink.set_volume(volume);
+        sink.append(self.decoder()?.repeat_infinite());
+        Ok(sink)
+    }
+}
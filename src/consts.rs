@@ -1,18 +1,52 @@
 use std::f32::consts::PI;
 
 pub const DESIRED_FPS: u32 = 60;
+pub const WINDOW_SIZE: f32 = 500.0;
+pub const SCREEN_SIZE: f32 = 1000.0;
 pub const DEFAULT_TOLERANCE: f32 = 0.1;
 pub const LEVEL_FILL_RADIUS: f32 = 25.0;
 pub const LEVEL_SPACE_RADIUS: f32 = 20.0;
 pub const LEVEL_RADIUS: f32 = LEVEL_FILL_RADIUS + LEVEL_SPACE_RADIUS;
+pub const PATTERN_STEP: f32 = 14.0;
+pub const PATTERN_WIDTH: f32 = 3.0;
 pub const FINAL_RADIUS: f32 = 80.0;
 pub const HORIZONTAL_SPEED: f32 = 0.16;
 pub const HORIZONTAL_SPEED_MIN: f32 = 0.04;
 pub const HORIZONTAL_SPEED_MAX: f32 = 0.20;
 pub const VERTICAL_SPEED: f32 = 3.;
+pub const STICK_DEADZONE: f32 = 0.2;
+pub const AIM_PRECISION: f32 = 1000.0;
 pub const GAME_TIME: u64 = 15;
+pub const TIMER_LOW: u64 = 5;
+/// Longest run of one input a replay may hold, a few level time limits.
+pub const REPLAY_MAX_RUN: usize = 4 * GAME_TIME as usize * DESIRED_FPS as usize;
+pub const WALL_DENSITY: f32 = 0.5;
 pub const LIFE_SIZE: u32 = 4;
 pub const PLAYER_LIFE: u32 = 3;
 pub const PLAYER_START_LEVEL: i32 = 8;
 pub const PLAYER_START_ANGLE: f32 = 0.5 * PI;
 pub const PI_2: f32 = 2.0 * PI;
+pub const EDITOR_RINGS: i32 = 7;
+pub const EDITOR_ANGLE_STEP: f32 = 0.05;
+pub const EDITOR_SEGMENT_SIZE: f32 = 0.4;
+pub const EDITOR_SPEED_STEP: f32 = 0.002;
+pub const HIGH_SCORES: usize = 10;
+pub const NAME_LENGTH: usize = 10;
+pub const VOLUME_STEP: f32 = 0.1;
+pub const CROSSFADE_TIME: f32 = 1.5;
+pub const DUCK_TIME: f32 = 0.5;
+pub const DUCK_VOLUME: f32 = 0.4;
+pub const PAN_WIDTH: f32 = 0.8;
+pub const RING_PITCH_STEP: f32 = 0.04;
+pub const GAME_SPEED_MIN: f32 = 0.5;
+pub const GAME_SPEED_MAX: f32 = 1.5;
+pub const GAME_SPEED_STEP: f32 = 0.1;
+pub const MENU_ITEM_SIZE: f32 = 40.0;
+pub const MENU_LEFT: f32 = 360.0;
+pub const MENU_DESCRIPTION_SIZE: f32 = 25.0;
+/// Distance between description lines, in multiples of the font size.
+pub const MENU_LINE_SPACING: f32 = 1.6;
+/// Advance of one Monaco character, in multiples of the font size.
+pub const MENU_CHAR_WIDTH: f32 = 0.6;
+/// Room kept for the control and theme hints at the bottom of menus.
+pub const MENU_FOOTER: f32 = 60.0;
@@ -3,7 +3,7 @@ use std::mem;
 
 use specs::{prelude::*, Component};
 
-use crate::consts::{PLAYER_START_ANGLE, PLAYER_START_LEVEL};
+use crate::levels::PlayerStart;
 use crate::utils::{self, Direction};
 
 #[derive(Debug, PartialEq, Eq)]
@@ -12,12 +12,6 @@ pub enum Form {
     Circle,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub enum CollisionType {
-    Wall,
-    Enemy,
-}
-
 #[derive(Component, Debug)]
 #[storage(VecStorage)]
 pub struct Player {
@@ -25,7 +19,6 @@ pub struct Player {
     pub speed: f32,
     pub speed_press_ms: f32,
     pub start_angle_repeat: f32,
-    pub collision: Option<CollisionType>,
 }
 
 impl Player {
@@ -90,8 +83,8 @@ impl Position {
         self.radius = utils::get_level_radius(level);
     }
 
-    pub fn set_default_player(&mut self) {
-        self.angle = PLAYER_START_ANGLE;
-        self.set_level(PLAYER_START_LEVEL);
+    pub fn set_default_player(&mut self, start: &PlayerStart) {
+        self.angle = start.angle;
+        self.set_level(start.level);
     }
 }
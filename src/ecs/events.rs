@@ -0,0 +1,25 @@
+use specs::shrev::EventChannel;
+
+/// Something that happened in play. Audio, effects and stats each read
+/// `GameEvents` with their own reader, so none of them miss an event.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameEvent {
+    /// The player bounced off a wall at this angle and radius.
+    WallHit {
+        angle: f32,
+        radius: f32,
+    },
+    /// The player ran into an enemy at this angle and radius.
+    EnemyHit {
+        angle: f32,
+        radius: f32,
+    },
+    LifeLost {
+        life: u32,
+    },
+    LevelCompleted,
+    /// The level timer is at or below `TIMER_LOW` seconds, once per level.
+    TimerLow,
+}
+
+pub type GameEvents = EventChannel<GameEvent>;
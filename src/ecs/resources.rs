@@ -1,17 +1,91 @@
+use std::collections::HashSet;
 use std::time;
 
-use ggez::audio;
+use ggez::conf::FullscreenType;
+use ggez::graphics::Rect;
 use ggez::input::keyboard::{KeyCode, KeyMods};
 use ggez::mint as mt;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rodio::OutputStreamHandle;
+use serde::{Deserialize, Serialize};
 
-use crate::consts::{GAME_TIME, PLAYER_START_ANGLE, PLAYER_START_LEVEL};
+use crate::audio::Clip;
+use crate::consts::{
+    DESIRED_FPS, EDITOR_ANGLE_STEP, EDITOR_RINGS, EDITOR_SEGMENT_SIZE, EDITOR_SPEED_STEP,
+    GAME_TIME, HIGH_SCORES, MENU_CHAR_WIDTH, MENU_DESCRIPTION_SIZE, MENU_ITEM_SIZE, MENU_LEFT,
+    MENU_LINE_SPACING, PI_2, SCREEN_SIZE,
+};
+use crate::input::{Bindings, InputAction};
+use crate::levels::{Level, Movement, PlayerStart, Ring, RingKind, Rotation};
+use crate::mixer::Mixer;
+use crate::replay::{Replay, Tick};
+use crate::themes::Themes;
 use crate::utils;
 
 #[derive(Debug, Default)]
 pub struct KeyState {
+    /// Last key that went down since the last tick, key repeat included.
     pub key: Option<KeyCode>,
     pub mods: Option<KeyMods>,
-    pub repeat: bool,
+    /// Keys held down.
+    pub held: HashSet<KeyCode>,
+    /// Keys that went down since the last tick, key repeat excluded.
+    pub just_pressed: HashSet<KeyCode>,
+    /// Keys that went up since the last tick.
+    pub just_released: HashSet<KeyCode>,
+    /// Actions pressed this tick on a gamepad or with the mouse.
+    pub pressed: Vec<InputAction>,
+    /// Rotation held on the gamepad d-pad or left stick.
+    pub pad_move: Option<InputAction>,
+    /// Left stick deflection in hundredths, `0.0` when the d-pad steers.
+    pub stick: f32,
+    /// Angle of the cursor around the arena centre, for mouse control.
+    pub aim: Option<f32>,
+}
+
+impl KeyState {
+    /// Stand in for a key press with actions from a gamepad or the mouse.
+    pub fn press(&mut self, actions: Vec<InputAction>) {
+        self.key = None;
+        self.mods = None;
+        self.pressed = actions;
+    }
+
+    /// Forget the edges and presses of the tick that just ended.
+    pub fn next_tick(&mut self) {
+        self.key = None;
+        self.mods = None;
+        self.just_pressed.clear();
+        self.just_released.clear();
+        self.pressed.clear();
+    }
+
+    /// Let go of everything held on the keyboard and gamepad.
+    pub fn release_all(&mut self) {
+        self.just_released.extend(self.held.drain());
+        self.pad_move = None;
+        self.stick = 0.0;
+    }
+
+    /// Whether `action` is down during this tick: held on the keyboard,
+    /// tapped and released before the tick ended, or pressed on a gamepad
+    /// or with the mouse.
+    pub fn holds(&self, bindings: &Bindings, action: InputAction) -> bool {
+        let keys = bindings.keys(action);
+        self.pressed.contains(&action)
+            || self
+                .held
+                .iter()
+                .chain(self.just_pressed.intersection(&self.just_released))
+                .any(|key| keys.contains(key))
+    }
+
+    /// Whether `action` went down during this tick.
+    pub fn started(&self, bindings: &Bindings, action: InputAction) -> bool {
+        let keys = bindings.keys(action);
+        self.pressed.contains(&action) || self.just_pressed.iter().any(|key| keys.contains(key))
+    }
 }
 
 pub struct GameState {
@@ -20,6 +94,8 @@ pub struct GameState {
     pub status: Option<utils::GameStatus>,
     pub theme: utils::Theme,
     pub control: utils::Control,
+    pub enemy_pattern: bool,
+    pub mode: utils::Mode,
 }
 
 impl Default for GameState {
@@ -28,8 +104,10 @@ impl Default for GameState {
             game_level: 1,
             score: 0,
             status: None,
-            theme: utils::Theme::Dark,
+            theme: utils::Theme::default(),
             control: utils::Control::Normal,
+            enemy_pattern: false,
+            mode: utils::Mode::Endless,
         }
     }
 }
@@ -41,19 +119,182 @@ impl GameState {
     }
 }
 
+/// Preferences saved between launches. Missing keys take their defaults
+/// and unknown ones are ignored, so files from other versions still load.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Name of a theme in `themes/`.
+    pub theme: String,
+    pub control: utils::Control,
+    /// Hatch enemy arcs, so they differ from walls by more than colour.
+    pub enemy_pattern: bool,
+    /// Multiplier of `DESIRED_FPS`, so the whole game runs slower or faster.
+    pub game_speed: f32,
+    pub fullscreen: bool,
+    /// Raise the pitch of collision effects on rings closer to the center.
+    pub ring_pitch: bool,
+    pub mixer: Mixer,
+    /// Set when a setting is changed and it's time to save the file.
+    #[serde(skip)]
+    pub changed: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            theme: "dark".to_string(),
+            control: utils::Control::Normal,
+            enemy_pattern: false,
+            game_speed: 1.0,
+            fullscreen: false,
+            ring_pitch: false,
+            mixer: Mixer::default(),
+            changed: false,
+        }
+    }
+}
+
+impl Settings {
+    pub const PATH: &'static str = "/settings.toml";
+
+    pub fn apply(&self, gs: &mut GameState, themes: &Themes) {
+        gs.theme = themes.get(&self.theme);
+        gs.control = self.control.clone();
+        gs.enemy_pattern = self.enemy_pattern;
+    }
+
+    pub fn updates_per_second(&self) -> u32 {
+        ((DESIRED_FPS as f32 * self.game_speed).round() as u32).max(1)
+    }
+
+    pub fn fullscreen_type(&self) -> FullscreenType {
+        if self.fullscreen {
+            FullscreenType::Desktop
+        } else {
+            FullscreenType::Windowed
+        }
+    }
+}
+
+/// Size of the window in pixels. Scenes draw in a `SCREEN_SIZE` square
+/// that is scaled to fit the window and centred in it.
+#[derive(Debug, Clone, Copy)]
+pub struct Screen {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for Screen {
+    fn default() -> Self {
+        Screen {
+            width: SCREEN_SIZE,
+            height: SCREEN_SIZE,
+        }
+    }
+}
+
+impl Screen {
+    fn scale(&self) -> f32 {
+        (self.width.min(self.height) / SCREEN_SIZE).max(f32::EPSILON)
+    }
+
+    /// Visible area in screen coordinates, wider or taller than the
+    /// square when the window isn't square.
+    pub fn rect(&self) -> Rect {
+        let scale = self.scale();
+        let (w, h) = (self.width / scale, self.height / scale);
+        Rect::new((SCREEN_SIZE - w) / 2.0, (SCREEN_SIZE - h) / 2.0, w, h)
+    }
+
+    /// Window pixel position in screen coordinates.
+    pub fn screen_point(&self, x: f32, y: f32) -> (f32, f32) {
+        let rect = self.rect();
+        let scale = self.scale();
+        (rect.x + x / scale, rect.y + y / scale)
+    }
+
+    pub fn center(&self) -> [f32; 2] {
+        [SCREEN_SIZE / 2.0, SCREEN_SIZE / 2.0]
+    }
+}
+
+/// Seed of the current run; every generated ring map is derived from it.
+#[derive(Debug, Clone, Copy)]
+pub struct Seed {
+    pub value: u64,
+    pub fixed: bool,
+}
+
+impl Default for Seed {
+    fn default() -> Self {
+        Seed {
+            value: Seed::random(),
+            fixed: false,
+        }
+    }
+}
+
+impl Seed {
+    pub fn new(value: u64) -> Self {
+        Seed { value, fixed: true }
+    }
+
+    fn random() -> u64 {
+        rand::thread_rng().gen::<u32>() as u64
+    }
+
+    /// Pick a new seed for the next run unless it was set on the command line.
+    pub fn reroll(&mut self) {
+        if !self.fixed {
+            self.value = Seed::random();
+        }
+    }
+
+    pub fn rng(&self, game_level: u32) -> StdRng {
+        // Spread the level over every bit, so seed N at level L+1 isn't seed N+1 at level L
+        let level = (game_level as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        StdRng::seed_from_u64(self.value ^ level)
+    }
+}
+
+/// Simulation clock of the current level, one tick per update.
+#[derive(Debug, Default)]
+pub struct Clock {
+    pub ticks: u64,
+}
+
+impl Clock {
+    /// Fixed simulated time between two updates.
+    pub fn step() -> time::Duration {
+        time::Duration::from_secs(1) / DESIRED_FPS
+    }
+
+    pub fn elapsed(&self) -> time::Duration {
+        time::Duration::from_secs(self.ticks) / DESIRED_FPS
+    }
+}
+
 #[derive(Debug)]
 pub struct GameTime {
-    pub last_instant: Option<time::Instant>,
-    pub delta: time::Duration,
+    pub limit: time::Duration,
     pub timer: time::Duration,
+    /// Whether `GameEvent::TimerLow` was sent for this level.
+    pub warned: bool,
 }
 
 impl Default for GameTime {
     fn default() -> Self {
+        Self::new(GAME_TIME)
+    }
+}
+
+impl GameTime {
+    pub fn new(secs: u64) -> Self {
         Self {
-            last_instant: None,
-            delta: time::Duration::new(0, 0),
-            timer: time::Duration::new(GAME_TIME, 0),
+            limit: time::Duration::new(secs, 0),
+            timer: time::Duration::new(secs, 0),
+            warned: false,
         }
     }
 }
@@ -76,14 +317,14 @@ impl Default for Curtain {
 }
 
 impl Curtain {
-    pub fn new_center_player() -> Self {
-        let player_radius = utils::get_level_radius(PLAYER_START_LEVEL);
+    pub fn new_center_player(start: &PlayerStart) -> Self {
+        let player_radius = utils::get_level_radius(start.level);
 
         Self {
             radius: 15.0,
             point: mt::Point2 {
-                x: PLAYER_START_ANGLE.cos() * player_radius,
-                y: PLAYER_START_ANGLE.sin() * player_radius,
+                x: start.angle.cos() * player_radius,
+                y: start.angle.sin() * player_radius,
             },
             constriction: 7.0,
         }
@@ -98,26 +339,103 @@ impl Curtain {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Action {
     EndlessMode,
     StoryMode,
+    Editor,
+    Chapter(usize),
+    Start,
+    Back,
     Continue,
+    Replay,
+    HighScores,
+    Options,
+    Theme,
+    Control,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    GameSpeed,
+    Fullscreen,
+    EnemyPattern,
+    RingPitch,
+    Keys,
+    Rebind(InputAction),
+    ResetKeys,
     Quit,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemKind {
+    Action,
+    Toggle(bool),
+    /// Moved by `step` within `min..=max`, shown as a percentage.
+    Slider {
+        value: f32,
+        min: f32,
+        max: f32,
+        step: f32,
+    },
+    Choice {
+        options: Vec<String>,
+        selected: usize,
+    },
+}
+
 #[derive(Debug)]
 pub struct MenuItem {
     pub action: Action,
     pub text: String,
     pub height: f32,
     pub available: bool,
+    pub kind: ItemKind,
+}
+
+impl MenuItem {
+    pub fn label(&self) -> String {
+        match &self.kind {
+            ItemKind::Action => self.text.clone(),
+            ItemKind::Toggle(on) => format!("{}: {}", self.text, if *on { "on" } else { "off" }),
+            ItemKind::Slider { value, .. } => {
+                format!("{}: < {:.0}% >", self.text, value * 100.0)
+            }
+            ItemKind::Choice { options, selected } => {
+                format!("{}: < {} >", self.text, options[*selected])
+            }
+        }
+    }
+
+    /// Change the value one step forward (`dir > 0`) or back.
+    pub fn adjust(&mut self, dir: i32) {
+        match &mut self.kind {
+            ItemKind::Action => {}
+            ItemKind::Toggle(on) => *on = !*on,
+            ItemKind::Slider {
+                value,
+                min,
+                max,
+                step,
+            } => {
+                let next = *value + *step * dir.signum() as f32;
+                // Round away float drift so values stay on the step grid
+                *value = ((next / *step).round() * *step).clamp(*min, *max);
+            }
+            ItemKind::Choice { options, selected } => {
+                let n = options.len() as i32;
+                *selected = (*selected as i32 + dir.signum()).rem_euclid(n) as usize;
+            }
+        }
+    }
 }
 
 #[derive(Default, Debug)]
 pub struct Menu {
     pub title: String,
     pub subtitle: String,
+    pub description: String,
+    /// Font size of the description; its line step follows from it.
+    pub description_size: f32,
     pub items: Vec<MenuItem>,
     pub current_item: usize,
 }
@@ -126,26 +444,343 @@ impl Menu {
     pub fn new(title: String) -> Self {
         Menu {
             title,
+            description_size: MENU_DESCRIPTION_SIZE,
             ..Default::default()
         }
     }
 
+    pub fn line_step(&self) -> f32 {
+        self.description_size * MENU_LINE_SPACING
+    }
+
     pub fn add_item(&mut self, action: Action, text: String, height: f32, available: bool) {
+        self.add_setting(action, text, height, ItemKind::Action);
+        self.items
+            .last_mut()
+            .expect("item was just added")
+            .available = available;
+    }
+
+    pub fn add_setting(&mut self, action: Action, text: String, height: f32, kind: ItemKind) {
         self.items.push(MenuItem {
             action,
             text,
             height,
-            available,
+            available: true,
+            kind,
         });
     }
 
+    pub fn adjust_current(&mut self, dir: i32) {
+        let current = self.current_item;
+        if let Some(item) = self.items.get_mut(current) {
+            item.adjust(dir);
+        }
+    }
+
+    /// Area of every item as drawn by `MenuRender`.
+    pub fn item_positions(&self) -> Vec<Rect> {
+        let mut y = 300.0;
+        if !self.subtitle.is_empty() {
+            y += 80.0;
+        }
+        if !self.description.is_empty() {
+            y += self.line_step() * (self.description.lines().count() + 1) as f32;
+        }
+        y += 60.0;
+
+        self.items
+            .iter()
+            .map(|item| {
+                y += 30.0 + item.height;
+                // Drawn as "> [label]" when selected
+                let chars = item.label().chars().count() + 4;
+                let width = chars as f32 * MENU_ITEM_SIZE * MENU_CHAR_WIDTH;
+                Rect::new(MENU_LEFT, y, width, MENU_ITEM_SIZE)
+            })
+            .collect()
+    }
+
+    /// Select the available item under the pointer, returning whether there is one.
+    pub fn hover(&mut self, x: f32, y: f32) -> bool {
+        let found = self
+            .item_positions()
+            .iter()
+            .position(|rect| rect.contains([x, y]));
+        match found {
+            Some(i) if self.items[i].available => {
+                self.current_item = i;
+                true
+            }
+            _ => false,
+        }
+    }
+
     pub fn get_currect_action(&self) -> Action {
         self.items[self.current_item].action.clone()
     }
+
+    pub fn is_current_available(&self) -> bool {
+        self.items[self.current_item].available
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default)]
+/// Collision effects, panned toward where on the ring they happen.
+/// Without an output device or a sound file they're just not played.
 pub struct Sound {
-    pub wall: Option<audio::Source>,
-    pub enemy: Option<audio::Source>,
+    pub output: Option<OutputStreamHandle>,
+    pub wall: Option<Clip>,
+    pub enemy: Option<Clip>,
+}
+
+/// Chapters in `story.toml` order. One that failed to load keeps its
+/// slot so the chapters after it, and saved progress, stay numbered.
+#[derive(Debug, Default, Clone)]
+pub struct Story {
+    pub chapters: Vec<Option<Level>>,
+}
+
+impl Story {
+    pub fn chapter(&self, chapter: usize) -> Option<&Level> {
+        self.chapters.get(chapter).and_then(Option::as_ref)
+    }
+
+    /// First chapter after `chapter` that can be played.
+    pub fn next_after(&self, chapter: usize) -> Option<usize> {
+        (chapter + 1..self.chapters.len()).find(|&next| self.chapter(next).is_some())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Progress {
+    pub unlocked: usize,
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Progress { unlocked: 1 }
+    }
+}
+
+impl Progress {
+    pub const PATH: &'static str = "/progress.toml";
+
+    pub fn unlock(&mut self, chapter: usize) {
+        self.unlocked = self.unlocked.max(chapter + 1);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Score {
+    pub name: String,
+    pub score: u64,
+    pub level: u32,
+    pub seed: u64,
+    pub date: String,
+}
+
+/// Best runs per mode, highest score first.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    #[serde(default)]
+    pub last_name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endless: Vec<Score>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub story: Vec<Score>,
+}
+
+impl HighScores {
+    pub const PATH: &'static str = "/highscores.toml";
+
+    pub fn table(&self, mode: utils::Mode) -> &[Score] {
+        match mode {
+            utils::Mode::Endless => &self.endless,
+            utils::Mode::Story(_) => &self.story,
+            utils::Mode::Test => &[],
+        }
+    }
+
+    fn table_mut(&mut self, mode: utils::Mode) -> Option<&mut Vec<Score>> {
+        match mode {
+            utils::Mode::Endless => Some(&mut self.endless),
+            utils::Mode::Story(_) => Some(&mut self.story),
+            utils::Mode::Test => None,
+        }
+    }
+
+    pub fn qualifies(&self, mode: utils::Mode, score: u64) -> bool {
+        if score == 0 || mode == utils::Mode::Test {
+            return false;
+        }
+        let table = self.table(mode);
+        table.len() < HIGH_SCORES || table.last().is_some_and(|s| score > s.score)
+    }
+
+    pub fn insert(&mut self, mode: utils::Mode, entry: Score) {
+        self.last_name = entry.name.clone();
+        if let Some(table) = self.table_mut(mode) {
+            let i = table.partition_point(|s| s.score >= entry.score);
+            table.insert(i, entry);
+            table.truncate(HIGH_SCORES);
+        }
+    }
+}
+
+/// Input of the run being played, turned into a replay when it ends.
+#[derive(Debug, Default)]
+pub struct Recorder {
+    replay: Option<Replay>,
+    ticks: Vec<(utils::Control, Tick)>,
+}
+
+impl Recorder {
+    pub fn start(&mut self, mode: utils::Mode, game_level: u32, seed: u64) {
+        self.replay = Some(Replay::new(mode, game_level, seed));
+        self.ticks.clear();
+    }
+
+    pub fn record(&mut self, control: &utils::Control, ks: &KeyState, bindings: &Bindings) {
+        if self.replay.is_some() {
+            self.ticks
+                .push((control.clone(), Tick::from_key_state(control, ks, bindings)));
+        }
+    }
+
+    pub fn finish(&mut self, score: u64) -> Option<Replay> {
+        let mut replay = self.replay.take()?;
+        replay.score = score;
+        replay.encode(&self.ticks);
+        self.ticks.clear();
+        Some(replay)
+    }
+}
+
+#[derive(Debug)]
+pub struct Editor {
+    pub level: Level,
+    pub ring: i32,
+    pub segment: usize,
+    pub message: String,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Editor {
+            level: Level {
+                name: "custom".to_string(),
+                ..Default::default()
+            },
+            ring: 1,
+            segment: 0,
+            message: String::new(),
+        }
+    }
+}
+
+impl Editor {
+    pub const PATH: &'static str = "/levels/custom.toml";
+
+    pub fn ring(&self) -> Option<&Ring> {
+        self.level.rings.iter().find(|r| r.level == self.ring)
+    }
+
+    pub fn ring_mut(&mut self) -> &mut Ring {
+        let level = self.ring;
+        let index = match self.level.rings.iter().position(|r| r.level == level) {
+            Some(index) => index,
+            None => {
+                self.level
+                    .rings
+                    .push(Ring::new(level, RingKind::Wall, None, &[]));
+                self.level.rings.len() - 1
+            }
+        };
+        &mut self.level.rings[index]
+    }
+
+    pub fn segment_mut(&mut self) -> Option<&mut (f32, f32)> {
+        let segment = self.segment;
+        self.ring_mut().segments.get_mut(segment)
+    }
+
+    pub fn select_ring(&mut self, ring: i32) {
+        self.ring = ring.clamp(1, EDITOR_RINGS);
+        self.segment = 0;
+    }
+
+    pub fn next_segment(&mut self) {
+        let count = self.ring().map_or(0, |r| r.segments.len());
+        if count > 0 {
+            self.segment = (self.segment + 1) % count;
+        }
+    }
+
+    pub fn rotate_segment(&mut self, step: f32) {
+        if let Some(segment) = self.segment_mut() {
+            segment.0 = utils::normalize_angle(segment.0 + step);
+        }
+    }
+
+    pub fn resize_segment(&mut self, step: f32) {
+        if let Some(segment) = self.segment_mut() {
+            segment.1 = (segment.1 + step).clamp(EDITOR_ANGLE_STEP, PI_2 - EDITOR_ANGLE_STEP);
+        }
+    }
+
+    pub fn add_segment(&mut self) {
+        let angle = match self.ring().and_then(|r| r.segments.get(self.segment)) {
+            Some((angle, size)) => utils::normalize_angle(angle + size + EDITOR_ANGLE_STEP),
+            None => 0.0,
+        };
+        let ring = self.ring_mut();
+        ring.segments.push((angle, EDITOR_SEGMENT_SIZE));
+        self.segment = ring.segments.len() - 1;
+    }
+
+    pub fn delete_segment(&mut self) {
+        let segment = self.segment;
+        let ring = self.ring_mut();
+        if segment < ring.segments.len() {
+            ring.segments.remove(segment);
+        }
+        self.segment = segment.saturating_sub(1);
+    }
+
+    pub fn toggle_kind(&mut self) {
+        let ring = self.ring_mut();
+        ring.kind = match ring.kind {
+            RingKind::Enemy => RingKind::Wall,
+            RingKind::Wall => RingKind::Enemy,
+        };
+    }
+
+    /// Cycle the ring rotation through static, left and right.
+    pub fn toggle_movement(&mut self) {
+        let ring = self.ring_mut();
+        ring.movement = match ring.movement {
+            None => Some(Movement {
+                direction: Rotation::Left,
+                speed: EDITOR_SPEED_STEP * 5.0,
+            }),
+            Some(Movement {
+                direction: Rotation::Left,
+                speed,
+            }) => Some(Movement {
+                direction: Rotation::Right,
+                speed,
+            }),
+            Some(Movement {
+                direction: Rotation::Right,
+                ..
+            }) => None,
+        };
+    }
+
+    pub fn change_speed(&mut self, step: f32) {
+        if let Some(movement) = self.ring_mut().movement.as_mut() {
+            movement.speed = (movement.speed + step).max(EDITOR_SPEED_STEP);
+        }
+    }
 }
@@ -2,21 +2,28 @@ use std::cmp::Ordering;
 use std::convert::TryInto;
 use std::time;
 
-use ggez::audio::SoundSource;
 use ggez::graphics::DrawParam;
 use ggez::graphics::{self, DrawMode, MeshBuilder};
-use ggez::input::keyboard::KeyCode;
+use ggez::input::keyboard::{KeyCode, KeyMods};
 use ggez::mint as mt;
 use ggez::Context;
+use log::warn;
 use specs::prelude::*;
 
 use crate::consts;
-use crate::ecs::components::{
-    CollisionType, ConstantMovement, Enemy, Form, Player, Position, View,
+use crate::ecs::components::{ConstantMovement, Enemy, Form, Player, Position, View};
+use crate::ecs::events::{GameEvent, GameEvents};
+use crate::ecs::resources::{
+    Clock, Curtain, Editor, GameState, GameTime, KeyState, Menu, Screen, Settings, Sound,
 };
-use crate::ecs::resources::{Curtain, GameState, GameTime, KeyState, Menu, Sound};
+use crate::input::{Bindings, InputAction};
+use crate::levels::{PlayerStart, RingKind};
+use crate::mixer::Bus;
+use crate::replay::Tick;
 use crate::shapes;
-use crate::utils::{self, Colour, Control, Direction, GameStatus, Theme};
+use crate::soundtrack::Soundtrack;
+use crate::themes::Themes;
+use crate::utils::{self, Colour, Control, Direction, GameStatus};
 
 pub struct UpdatePosition;
 
@@ -24,52 +31,79 @@ impl<'a> System<'a> for UpdatePosition {
     type SystemData = (
         Read<'a, GameState>,
         Read<'a, KeyState>,
-        Read<'a, GameTime>,
+        Read<'a, Bindings>,
         WriteStorage<'a, Player>,
         ReadStorage<'a, ConstantMovement>,
         WriteStorage<'a, Position>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (gs, ks, gt, mut player, conmove, mut pos) = data;
+        let (gs, ks, bindings, mut player, conmove, mut pos) = data;
+        let tick = Tick::from_key_state(&gs.control, &ks, &bindings);
 
         for (pl, pos) in (&mut player, &mut pos).join() {
-            let mut dir = match ks.key {
-                Some(KeyCode::H) | Some(KeyCode::Left) | Some(KeyCode::Numpad4) => {
-                    UpdatePosition::set_speed_boost(pl, ks.repeat, gt.delta);
+            let mut turn = match tick.rotation {
+                Some(InputAction::MoveClockwise) => {
+                    UpdatePosition::set_speed(pl, tick.repeat, tick.stick());
                     Some(Direction::Left(pl.speed))
                 }
-                Some(KeyCode::L) | Some(KeyCode::Right) | Some(KeyCode::Numpad6) => {
-                    UpdatePosition::set_speed_boost(pl, ks.repeat, gt.delta);
+                Some(InputAction::MoveCounterClockwise) => {
+                    UpdatePosition::set_speed(pl, tick.repeat, tick.stick());
                     Some(Direction::Right(pl.speed))
                 }
-                Some(KeyCode::K) | Some(KeyCode::Down) | Some(KeyCode::Numpad2) => None,
-                Some(KeyCode::J) | Some(KeyCode::Up) | Some(KeyCode::Numpad8) => {
-                    Some(Direction::Up)
-                }
+                Some(_) => None,
+                None => None,
+            };
+            let mut step = match tick.step {
+                Some(InputAction::MoveInward) => Some(Direction::Up),
                 Some(_) => None,
                 None => None,
             };
 
-            if !ks.repeat {
+            if !tick.repeat {
                 pl.start_angle_repeat = pos.angle;
             }
-            if gs.control == Control::Advanced {
-                dir = utils::get_dir(ks.key, pl.start_angle_repeat, consts::HORIZONTAL_SPEED);
+            match gs.control {
+                Control::Advanced => {
+                    turn = None;
+                    step = utils::get_dir(
+                        tick.action(),
+                        pl.start_angle_repeat,
+                        consts::HORIZONTAL_SPEED,
+                    );
+                }
+                Control::Mouse => {
+                    turn = tick.aim().and_then(|aim| {
+                        utils::aim_dir(aim, pos.angle, consts::HORIZONTAL_SPEED_MAX)
+                    });
+                }
+                Control::Normal => {}
             }
-            self.to_move(pos, dir);
+            self.to_move(pos, &[turn, step]);
         }
 
         for (cm, pos) in (&conmove, &mut pos).join() {
-            self.to_move(pos, Some(cm.direction));
+            self.to_move(pos, &[Some(cm.direction)]);
         }
     }
 }
 
 impl UpdatePosition {
+    /// Speed follows the stick deflection, keys and the d-pad speed up
+    /// while held.
+    pub fn set_speed(pl: &mut Player, repeat: bool, stick: f32) {
+        if stick > 0.0 {
+            pl.speed_press_ms = 0.0;
+            pl.speed = consts::HORIZONTAL_SPEED_MIN
+                + (consts::HORIZONTAL_SPEED_MAX - consts::HORIZONTAL_SPEED_MIN) * stick;
+        } else {
+            UpdatePosition::set_speed_boost(pl, repeat, Clock::step());
+        }
+    }
+
     pub fn set_speed_boost(pl: &mut Player, repeat: bool, delta: time::Duration) {
         if repeat {
-            pl.speed_press_ms += delta.subsec_millis() as f32;
+            pl.speed_press_ms += delta.as_secs_f32() * 1000.0;
             let u = (pl.speed_press_ms / 100.0).ceil() / 100.0;
             pl.speed = (consts::HORIZONTAL_SPEED_MIN + u).min(consts::HORIZONTAL_SPEED_MAX);
         } else {
@@ -78,12 +112,19 @@ impl UpdatePosition {
         }
     }
 
-    pub fn to_move(&self, pos: &mut Position, dir: Option<Direction>) {
+    /// Apply `dirs` in order, unless the player is still moving between rings.
+    pub fn to_move(&self, pos: &mut Position, dirs: &[Option<Direction>]) {
         if pos.is_between_level() {
             self.update_radius(pos);
             return;
         }
 
+        for dir in dirs {
+            self.apply(pos, *dir);
+        }
+    }
+
+    fn apply(&self, pos: &mut Position, dir: Option<Direction>) {
         match dir {
             Some(Direction::Up) => {
                 pos.next_level -= 1;
@@ -127,14 +168,16 @@ pub struct Collision;
 impl<'a> System<'a> for Collision {
     type SystemData = (
         Entities<'a>,
+        Read<'a, PlayerStart>,
         WriteStorage<'a, Player>,
         ReadStorage<'a, Enemy>,
         ReadStorage<'a, View>,
         WriteStorage<'a, Position>,
+        Write<'a, GameEvents>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut player, enemy, view, mut position) = data;
+        let (entities, spawn, mut player, enemy, view, mut position, mut events) = data;
 
         let find_levels = (&player, &mut position)
             .join()
@@ -176,9 +219,7 @@ impl<'a> System<'a> for Collision {
                             utils::normalize_angle(player_points[1].y.atan2(player_points[1].x));
                         p.angle = (pa - p.angle) + sa;
                         if *is_enemy {
-                            pl.collision = Some(CollisionType::Enemy);
-                            pl.take_life();
-                            p.set_default_player();
+                            self.hit_enemy(pl, p, &spawn, &mut events);
                         }
                         break;
                     } else if self.is_body_collision(*end, &player_points) {
@@ -187,9 +228,7 @@ impl<'a> System<'a> for Collision {
                             utils::normalize_angle(player_points[3].y.atan2(player_points[3].x));
                         p.angle = sa - (p.angle - pa);
                         if *is_enemy {
-                            pl.collision = Some(CollisionType::Enemy);
-                            pl.take_life();
-                            p.set_default_player();
+                            self.hit_enemy(pl, p, &spawn, &mut events);
                         }
                         break;
                     }
@@ -198,21 +237,16 @@ impl<'a> System<'a> for Collision {
                         && self.is_radius_collision(p.angle, *start, *end)
                     {
                         if *is_enemy {
-                            pl.collision = Some(CollisionType::Enemy);
-                            pl.take_life();
-                            p.set_default_player();
+                            self.hit_enemy(pl, p, &spawn, &mut events);
                         } else {
-                            pl.collision = Some(CollisionType::Wall);
-                            p.swap_level();
+                            self.hit_wall(p, &mut events);
                         }
                         break;
                     }
                     if p.current_level < p.next_level
                         && self.is_radius_collision(p.angle, *start, *end)
                     {
-                        pl.collision = Some(CollisionType::Enemy);
-                        pl.take_life();
-                        p.set_default_player();
+                        self.hit_enemy(pl, p, &spawn, &mut events);
                         break;
                     }
                     if p.radius < space_radius
@@ -220,12 +254,9 @@ impl<'a> System<'a> for Collision {
                             || self.is_body_collision(*end, &player_points))
                     {
                         if *is_enemy {
-                            pl.collision = Some(CollisionType::Enemy);
-                            pl.take_life();
-                            p.set_default_player();
+                            self.hit_enemy(pl, p, &spawn, &mut events);
                         } else {
-                            pl.collision = Some(CollisionType::Wall);
-                            p.swap_level();
+                            self.hit_wall(p, &mut events);
                         }
                         break;
                     }
@@ -236,6 +267,32 @@ impl<'a> System<'a> for Collision {
 }
 
 impl Collision {
+    fn hit_enemy(
+        &self,
+        pl: &mut Player,
+        p: &mut Position,
+        spawn: &PlayerStart,
+        events: &mut GameEvents,
+    ) {
+        // Reported where the hit happened, before the player respawns
+        events.single_write(GameEvent::EnemyHit {
+            angle: p.angle,
+            radius: p.radius,
+        });
+        if let Some(life) = pl.take_life() {
+            events.single_write(GameEvent::LifeLost { life });
+        }
+        p.set_default_player(spawn);
+    }
+
+    fn hit_wall(&self, p: &mut Position, events: &mut GameEvents) {
+        events.single_write(GameEvent::WallHit {
+            angle: p.angle,
+            radius: p.radius,
+        });
+        p.swap_level();
+    }
+
     pub fn is_between_angle(&self, mid: f32, start: f32, end: f32) -> bool {
         let r = consts::PI_2;
         let e = if (end - start) < 0.0 {
@@ -298,25 +355,28 @@ impl Collision {
     }
 }
 
-pub struct UpdateTimer;
+pub struct UpdateClock;
 
-impl<'a> System<'a> for UpdateTimer {
-    type SystemData = Write<'a, GameTime>;
+impl<'a> System<'a> for UpdateClock {
+    type SystemData = Write<'a, Clock>;
 
-    fn run(&mut self, mut gt: Self::SystemData) {
-        if gt.last_instant.is_none() {
-            gt.last_instant = Some(time::Instant::now())
-        }
-        let now = time::Instant::now();
-        gt.delta = now - gt.last_instant.expect("last instant is none");
-        gt.last_instant = Some(now);
+    fn run(&mut self, mut clock: Self::SystemData) {
+        clock.ticks += 1;
+    }
+}
 
-        if let Some(delta) = gt.timer.checked_sub(gt.delta) {
-            gt.timer = delta;
-            return;
-        };
+pub struct UpdateTimer;
 
-        gt.timer = time::Duration::default();
+impl<'a> System<'a> for UpdateTimer {
+    type SystemData = (Read<'a, Clock>, Write<'a, GameTime>, Write<'a, GameEvents>);
+
+    fn run(&mut self, (clock, mut gt, mut events): Self::SystemData) {
+        gt.timer = gt.limit.saturating_sub(clock.elapsed());
+        // Also sent on the first tick of a level that starts short on time
+        if !gt.warned && gt.timer <= time::Duration::from_secs(consts::TIMER_LOW) {
+            gt.warned = true;
+            events.single_write(GameEvent::TimerLow);
+        }
     }
 }
 
@@ -328,10 +388,11 @@ impl<'a> System<'a> for UpdateGameState {
         Read<'a, GameTime>,
         ReadStorage<'a, Player>,
         ReadStorage<'a, Position>,
+        Write<'a, GameEvents>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut gs, gt, player, position) = data;
+        let (mut gs, gt, player, position, mut events) = data;
 
         if gt.timer == time::Duration::default() {
             gs.status = Some(GameStatus::GameOver);
@@ -345,29 +406,37 @@ impl<'a> System<'a> for UpdateGameState {
                 gs.status = Some(GameStatus::LevelCompleted);
             }
         }
+        if gs.status == Some(GameStatus::LevelCompleted) {
+            events.single_write(GameEvent::LevelCompleted);
+        }
     }
 }
 
 pub struct UpdateGlobalState;
 
 impl<'a> System<'a> for UpdateGlobalState {
-    type SystemData = (Write<'a, GameState>, Read<'a, KeyState>);
+    type SystemData = (
+        Write<'a, GameState>,
+        Write<'a, Settings>,
+        Read<'a, KeyState>,
+        Read<'a, Themes>,
+    );
 
-    fn run(&mut self, (mut gs, ks): Self::SystemData) {
+    fn run(&mut self, (mut gs, mut settings, ks, themes): Self::SystemData) {
         match ks.key {
             Some(KeyCode::F2) => {
-                gs.control = if gs.control == Control::Normal {
-                    Control::Advanced
-                } else {
-                    Control::Normal
-                }
+                gs.control = match gs.control {
+                    Control::Normal => Control::Advanced,
+                    Control::Advanced => Control::Mouse,
+                    Control::Mouse => Control::Normal,
+                };
+                settings.control = gs.control.clone();
+                settings.changed = true;
             }
             Some(KeyCode::F3) => {
-                gs.theme = if gs.theme == Theme::Dark {
-                    Theme::Light
-                } else {
-                    Theme::Dark
-                }
+                gs.theme = themes.next(&gs.theme.name);
+                settings.theme = gs.theme.name.clone();
+                settings.changed = true;
             }
             Some(_) => (),
             None => (),
@@ -388,18 +457,73 @@ impl<'a> System<'a> for UpdateCurtain {
 pub struct UpdateMenu;
 
 impl<'a> System<'a> for UpdateMenu {
-    type SystemData = (Read<'a, KeyState>, Write<'a, Menu>);
-
-    fn run(&mut self, (ks, mut menu): Self::SystemData) {
-        match ks.key {
-            Some(KeyCode::K) | Some(KeyCode::Down) | Some(KeyCode::Numpad2) => {
+    type SystemData = (Read<'a, KeyState>, Read<'a, Bindings>, Write<'a, Menu>);
+
+    fn run(&mut self, (ks, bindings, mut menu): Self::SystemData) {
+        // Confirm goes first, so a button bound to it and a move only confirms
+        let pressed = bindings.find(
+            &ks,
+            &[
+                InputAction::Confirm,
+                InputAction::MoveOutward,
+                InputAction::MoveInward,
+                InputAction::MoveClockwise,
+                InputAction::MoveCounterClockwise,
+            ],
+        );
+        match pressed {
+            Some(InputAction::MoveOutward) => {
                 menu.current_item = (menu.current_item + 1).min(menu.items.len() - 1);
             }
-            Some(KeyCode::J) | Some(KeyCode::Up) | Some(KeyCode::Numpad8) => {
+            Some(InputAction::MoveInward) => {
                 if let Some(x) = menu.current_item.checked_sub(1) {
                     menu.current_item = x;
                 };
             }
+            Some(InputAction::MoveClockwise) => menu.adjust_current(-1),
+            Some(InputAction::MoveCounterClockwise) => menu.adjust_current(1),
+            Some(_) => (),
+            None => (),
+        };
+    }
+}
+
+pub struct UpdateEditor;
+
+impl<'a> System<'a> for UpdateEditor {
+    type SystemData = (Read<'a, KeyState>, Read<'a, Bindings>, Write<'a, Editor>);
+
+    fn run(&mut self, (ks, bindings, mut editor): Self::SystemData) {
+        let shift = ks.mods.is_some_and(|m| m.contains(KeyMods::SHIFT));
+        let step = consts::EDITOR_ANGLE_STEP;
+
+        if let Some(action) = bindings.movement(&ks) {
+            match action {
+                InputAction::MoveInward => {
+                    let ring = editor.ring - 1;
+                    editor.select_ring(ring);
+                }
+                InputAction::MoveOutward => {
+                    let ring = editor.ring + 1;
+                    editor.select_ring(ring);
+                }
+                InputAction::MoveClockwise if shift => editor.resize_segment(step),
+                InputAction::MoveClockwise => editor.rotate_segment(step),
+                InputAction::MoveCounterClockwise if shift => editor.resize_segment(-step),
+                InputAction::MoveCounterClockwise => editor.rotate_segment(-step),
+                _ => (),
+            }
+            return;
+        }
+
+        match ks.key {
+            Some(KeyCode::Tab) => editor.next_segment(),
+            Some(KeyCode::A) | Some(KeyCode::Insert) => editor.add_segment(),
+            Some(KeyCode::X) | Some(KeyCode::Delete) => editor.delete_segment(),
+            Some(KeyCode::E) => editor.toggle_kind(),
+            Some(KeyCode::D) => editor.toggle_movement(),
+            Some(KeyCode::LBracket) => editor.change_speed(-consts::EDITOR_SPEED_STEP),
+            Some(KeyCode::RBracket) => editor.change_speed(consts::EDITOR_SPEED_STEP),
             Some(_) => (),
             None => (),
         };
@@ -422,6 +546,7 @@ impl<'a, 'c> System<'a> for GameRender<'c> {
         Entities<'a>,
         Read<'a, GameState>,
         Read<'a, GameTime>,
+        Read<'a, Screen>,
         ReadStorage<'a, Enemy>,
         ReadStorage<'a, Position>,
         ReadStorage<'a, View>,
@@ -429,9 +554,10 @@ impl<'a, 'c> System<'a> for GameRender<'c> {
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, gs, gt, enemy, pos, view, player) = data;
+        let (entities, gs, gt, screen, enemy, pos, view, player) = data;
 
-        let size = self.ctx.gfx.size();
+        let [cx, cy] = screen.center();
+        let rect = screen.rect();
 
         let mesh = &mut MeshBuilder::new();
 
@@ -501,6 +627,20 @@ impl<'a, 'c> System<'a> for GameRender<'c> {
                     );
 
                     mesh.polyline(DrawMode::fill(), &points, color).unwrap();
+
+                    if is_enemy && gs.enemy_pattern {
+                        let lines = shapes::hatching(
+                            pos.radius,
+                            pos.angle,
+                            view.size,
+                            consts::LEVEL_FILL_RADIUS,
+                            consts::PATTERN_STEP,
+                        );
+                        for line in &lines {
+                            mesh.line(line, consts::PATTERN_WIDTH, Colour::Bg.value(&gs.theme))
+                                .unwrap();
+                        }
+                    }
                 }
             }
         }
@@ -510,7 +650,7 @@ impl<'a, 'c> System<'a> for GameRender<'c> {
         self.canvas.draw(
             &graphics::Mesh::from_data(self.ctx, ms),
             DrawParam::default()
-                .dest([size.0 / 2., size.1 / 2.])
+                .dest([cx, cy])
                 .color(Colour::Fg.value(&gs.theme)),
         );
 
@@ -520,7 +660,7 @@ impl<'a, 'c> System<'a> for GameRender<'c> {
                 .set_font("Monaco")
                 .set_scale(100.),
             DrawParam::default()
-                .dest([size.0 / 2. - 48., size.1 / 2. - 60.])
+                .dest([cx - 48., cy - 60.])
                 .color(Colour::Bg.value(&gs.theme)),
         );
 
@@ -529,7 +669,7 @@ impl<'a, 'c> System<'a> for GameRender<'c> {
                 .set_font("Monaco")
                 .set_scale(40.),
             DrawParam::default()
-                .dest([size.0 / 2. - 30., size.1 / 2. + 20.])
+                .dest([cx - 30., cy + 20.])
                 .color(Colour::Bg.value(&gs.theme)),
         );
 
@@ -539,7 +679,7 @@ impl<'a, 'c> System<'a> for GameRender<'c> {
                 .set_font("Monaco")
                 .set_scale(30.),
             DrawParam::default()
-                .dest([size.0 - size.0 / 8., 5.])
+                .dest([rect.right() - 125., rect.y + 5.])
                 .color(Colour::Fg.value(&gs.theme)),
         );
 
@@ -548,7 +688,7 @@ impl<'a, 'c> System<'a> for GameRender<'c> {
                 .set_font("Monaco")
                 .set_scale(30.),
             DrawParam::default()
-                .dest([size.0 - size.0 / 8., 30.])
+                .dest([rect.right() - 125., rect.y + 30.])
                 .color(Colour::Fg.value(&gs.theme)),
         );
     }
@@ -566,16 +706,20 @@ impl<'c> CurtainRender<'c> {
 }
 
 impl<'a, 'c> System<'a> for CurtainRender<'c> {
-    type SystemData = (Read<'a, GameState>, Read<'a, Curtain>);
+    type SystemData = (Read<'a, GameState>, Read<'a, Curtain>, Read<'a, Screen>);
 
-    fn run(&mut self, (gs, curtain): Self::SystemData) {
+    fn run(&mut self, (gs, curtain, screen): Self::SystemData) {
         let mesh = &mut MeshBuilder::new();
 
+        // Around the player, who is drawn relative to the arena centre
+        let [cx, cy] = screen.center();
+        // Wide enough to cover the whole window around the hole
+        let rect = screen.rect();
         let points = shapes::arc(
             curtain.radius,
             0.0,
             consts::PI_2,
-            1000.0,
+            rect.w + rect.h,
             false,
             consts::DEFAULT_TOLERANCE,
         );
@@ -601,27 +745,26 @@ impl<'a, 'c> System<'a> for CurtainRender<'c> {
         self.canvas.draw(
             &graphics::Mesh::from_data(self.ctx, ms),
             DrawParam::default()
-                .dest([curtain.point.x + 500., curtain.point.y + 90.])
+                .dest([cx + curtain.point.x, cy + curtain.point.y])
                 .color(Colour::Fg.value(&gs.theme)),
         );
     }
 }
 
 pub struct MenuRender<'c> {
-    ctx: &'c mut Context,
     canvas: &'c mut graphics::Canvas,
 }
 
 impl<'c> MenuRender<'c> {
-    pub fn new(ctx: &'c mut Context, canvas: &'c mut graphics::Canvas) -> MenuRender<'c> {
-        MenuRender { ctx, canvas }
+    pub fn new(canvas: &'c mut graphics::Canvas) -> MenuRender<'c> {
+        MenuRender { canvas }
     }
 }
 
 impl<'a, 'c> System<'a> for MenuRender<'c> {
-    type SystemData = (Read<'a, GameState>, Read<'a, Menu>);
+    type SystemData = (Read<'a, GameState>, Read<'a, Menu>, Read<'a, Screen>);
 
-    fn run(&mut self, (gs, menu): Self::SystemData) {
+    fn run(&mut self, (gs, menu, screen): Self::SystemData) {
         let mut y = 300.;
 
         self.canvas.draw(
@@ -629,7 +772,7 @@ impl<'a, 'c> System<'a> for MenuRender<'c> {
                 .set_font("Monaco")
                 .set_scale(60.),
             DrawParam::default()
-                .dest([360., y])
+                .dest([consts::MENU_LEFT, y])
                 .color(Colour::Fg.value(&gs.theme)),
         );
 
@@ -640,24 +783,37 @@ impl<'a, 'c> System<'a> for MenuRender<'c> {
                     .set_font("Monaco")
                     .set_scale(60.),
                 DrawParam::default()
-                    .dest([360., y])
+                    .dest([consts::MENU_LEFT, y])
                     .color(Colour::Fg.value(&gs.theme)),
             );
         }
 
-        y += 60.0;
-        for (i, item) in menu.items.iter().enumerate() {
-            y += 30.0 + item.height;
+        if !menu.description.is_empty() {
+            let step = menu.line_step();
+            for line in menu.description.lines() {
+                y += step;
+                self.canvas.draw(
+                    graphics::Text::new(line)
+                        .set_font("Monaco")
+                        .set_scale(menu.description_size),
+                    DrawParam::default()
+                        .dest([consts::MENU_LEFT, y + step])
+                        .color(Colour::Gray.value(&gs.theme)),
+                );
+            }
+        }
+
+        for (i, (item, rect)) in menu.items.iter().zip(menu.item_positions()).enumerate() {
             self.canvas.draw(
                 graphics::Text::new(if menu.current_item == i {
-                    format!("> [{}]", item.text)
+                    format!("> [{}]", item.label())
                 } else {
-                    format!("   {}", item.text)
+                    format!("   {}", item.label())
                 })
                 .set_font("Monaco")
-                .set_scale(40.),
+                .set_scale(consts::MENU_ITEM_SIZE),
                 DrawParam::default()
-                    .dest([360., y])
+                    .dest(rect.point())
                     .color(if item.available {
                         if menu.current_item == i {
                             Colour::Special.value(&gs.theme)
@@ -670,55 +826,209 @@ impl<'a, 'c> System<'a> for MenuRender<'c> {
             );
         }
 
+        // Control and theme hints side by side, centred in the footer
+        let size = consts::MENU_DESCRIPTION_SIZE;
+        let y = screen.rect().bottom() - (consts::MENU_FOOTER + size) / 2.0;
+        let mut x = consts::MENU_LEFT;
+        for hint in [
+            format!("[F2] control: {}", gs.control),
+            format!("[F3] theme: {}", gs.theme),
+        ] {
+            let width = (hint.chars().count() + 4) as f32 * size * consts::MENU_CHAR_WIDTH;
+            self.canvas.draw(
+                graphics::Text::new(hint).set_font("Monaco").set_scale(size),
+                DrawParam::default()
+                    .dest([x, y])
+                    .color(Colour::Fg.value(&gs.theme)),
+            );
+            x += width;
+        }
+    }
+}
+
+pub struct EditorRender<'c> {
+    ctx: &'c mut Context,
+    canvas: &'c mut graphics::Canvas,
+}
+
+impl<'c> EditorRender<'c> {
+    pub fn new(ctx: &'c mut Context, canvas: &'c mut graphics::Canvas) -> EditorRender<'c> {
+        EditorRender { ctx, canvas }
+    }
+}
+
+impl<'a, 'c> System<'a> for EditorRender<'c> {
+    type SystemData = (Read<'a, GameState>, Read<'a, Editor>, Read<'a, Screen>);
+
+    fn run(&mut self, (gs, editor, screen): Self::SystemData) {
+        let rect = screen.rect();
+
+        let mesh = &mut MeshBuilder::new();
+
+        mesh.circle(
+            DrawMode::fill(),
+            [0., 0.],
+            utils::get_level_radius(0) - 12.0,
+            consts::DEFAULT_TOLERANCE,
+            Colour::White.value(&gs.theme),
+        )
+        .unwrap();
+
+        for level in 1..=consts::EDITOR_RINGS {
+            let color = if level == editor.ring {
+                Colour::Border.value(&gs.theme)
+            } else {
+                Colour::Gray.value(&gs.theme)
+            };
+            mesh.circle(
+                DrawMode::stroke(1.0),
+                [0., 0.],
+                utils::get_level_radius(level) - consts::LEVEL_FILL_RADIUS / 2.0,
+                consts::DEFAULT_TOLERANCE,
+                color,
+            )
+            .unwrap();
+        }
+
+        for ring in &editor.level.rings {
+            for (i, (angle, size)) in ring.segments.iter().enumerate() {
+                let color = if ring.level == editor.ring && i == editor.segment {
+                    Colour::Special.value(&gs.theme)
+                } else if ring.kind == RingKind::Enemy {
+                    Colour::Enemy.value(&gs.theme)
+                } else {
+                    Colour::Fg.value(&gs.theme)
+                };
+
+                let points = shapes::arc(
+                    utils::get_level_radius(ring.level),
+                    *angle,
+                    *size,
+                    consts::LEVEL_FILL_RADIUS,
+                    true,
+                    consts::DEFAULT_TOLERANCE,
+                );
+
+                mesh.polyline(DrawMode::fill(), &points, color).unwrap();
+            }
+        }
+
+        let start = editor.level.player;
+        let points = shapes::player(
+            utils::get_level_radius(start.level),
+            start.angle,
+            8.0,
+            consts::LEVEL_FILL_RADIUS,
+        );
+        mesh.polyline(DrawMode::fill(), &points, Colour::Player.value(&gs.theme))
+            .unwrap();
+
+        let ms = mesh.build();
+
         self.canvas.draw(
-            graphics::Text::new(format!("[F2] control: {}", gs.control))
-                .set_font("Monaco")
-                .set_scale(25.),
+            &graphics::Mesh::from_data(self.ctx, ms),
+            DrawParam::default()
+                .dest(screen.center())
+                .color(Colour::Fg.value(&gs.theme)),
+        );
+
+        let info = match editor.ring() {
+            Some(ring) => {
+                let kind = match ring.kind {
+                    RingKind::Enemy => "enemy",
+                    RingKind::Wall => "wall",
+                };
+                let movement = match ring.movement {
+                    Some(m) => format!("{:?} {:.3}", m.direction, m.speed).to_lowercase(),
+                    None => "static".to_string(),
+                };
+                format!("ring {}: {}, {}", editor.ring, kind, movement)
+            }
+            None => format!("ring {}: empty", editor.ring),
+        };
+
+        self.canvas.draw(
+            graphics::Text::new(info).set_font("Monaco").set_scale(30.),
             DrawParam::default()
-                .dest([450., 950.])
+                .dest([rect.x + 20., rect.y + 20.])
                 .color(Colour::Fg.value(&gs.theme)),
         );
 
         self.canvas.draw(
-            graphics::Text::new(format!("[F3] theme: {}", gs.theme))
+            graphics::Text::new(editor.message.as_str())
                 .set_font("Monaco")
                 .set_scale(25.),
             DrawParam::default()
-                .dest([750., 950.])
-                .color(Colour::Fg.value(&gs.theme)),
+                .dest([rect.x + 20., rect.y + 55.])
+                .color(Colour::Special.value(&gs.theme)),
         );
+
+        let help = [
+            "[up/down] ring  [tab] segment  [left/right] move  [shift] resize",
+            "[a] add  [x] delete  [e] enemy/wall  [d] rotation  [ [ ] ] speed",
+            "[t] test  [s] save  [esc] back",
+        ];
+        for (i, line) in help.iter().enumerate() {
+            self.canvas.draw(
+                graphics::Text::new(*line).set_font("Monaco").set_scale(20.),
+                DrawParam::default()
+                    .dest([rect.x + 20., rect.bottom() - 90. + i as f32 * 25.])
+                    .color(Colour::Gray.value(&gs.theme)),
+            );
+        }
     }
 }
 
-pub struct Music<'c> {
-    ctx: &'c mut Context,
+/// Plays collision effects, reading them off `GameEvents`.
+#[derive(Default)]
+pub struct Music {
+    reader: Option<ReaderId<GameEvent>>,
 }
 
-impl<'c> Music<'c> {
-    pub fn new(ctx: &'c mut Context) -> Music<'c> {
-        Music { ctx }
-    }
-}
+impl<'a> System<'a> for Music {
+    type SystemData = (
+        Read<'a, Settings>,
+        Read<'a, Sound>,
+        Write<'a, Soundtrack>,
+        Read<'a, GameEvents>,
+    );
 
-impl<'a, 'c> System<'a> for Music<'c> {
-    type SystemData = (Write<'a, Sound>, WriteStorage<'a, Player>);
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(world.fetch_mut::<GameEvents>().register_reader());
+    }
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut sound, mut player) = data;
+        let (settings, sound, mut soundtrack, events) = data;
+        let reader = match self.reader.as_mut() {
+            Some(reader) => reader,
+            None => return,
+        };
 
-        for p in (&mut player).join() {
-            match p.collision {
-                Some(CollisionType::Enemy) => {
-                    let s = sound.enemy.as_mut().unwrap();
-                    s.play(self.ctx).unwrap();
-                }
-                Some(CollisionType::Wall) => {
-                    let s = sound.wall.as_mut().unwrap();
-                    s.play(self.ctx).unwrap();
-                }
-                None => {}
+        for event in events.read(reader) {
+            let (clip, angle, radius) = match *event {
+                GameEvent::EnemyHit { angle, radius } => (&sound.enemy, angle, radius),
+                GameEvent::WallHit { angle, radius } => (&sound.wall, angle, radius),
+                _ => continue,
+            };
+            soundtrack.duck();
+            let (output, clip) = match (&sound.output, clip) {
+                (Some(output), Some(clip)) => (output, clip),
+                _ => continue,
+            };
+
+            // Between the ears, which sit at x = -1 and x = 1
+            let pan = angle.cos() * consts::PAN_WIDTH;
+            let pitch = if settings.ring_pitch {
+                let rings = consts::PLAYER_START_LEVEL - utils::get_level(radius);
+                1.0 + consts::RING_PITCH_STEP * rings as f32
+            } else {
+                1.0
+            };
+            let volume = settings.mixer.volume(Bus::Sfx);
+            if let Err(e) = clip.play(output, pan, pitch, volume) {
+                warn!("Can't play sound: {}", e);
             }
-            p.collision = None;
         }
     }
 }
@@ -0,0 +1,280 @@
+use std::fmt;
+
+use ggez::event::Button;
+use ggez::input::keyboard::{KeyCode, KeyInput, KeyMods};
+use ggez::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::ecs::resources::KeyState;
+use crate::storage;
+
+/// What a key does, independent of which physical key it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputAction {
+    MoveClockwise,
+    MoveCounterClockwise,
+    MoveInward,
+    MoveOutward,
+    Confirm,
+    Pause,
+    Back,
+    Mute,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 8] = [
+        InputAction::MoveClockwise,
+        InputAction::MoveCounterClockwise,
+        InputAction::MoveInward,
+        InputAction::MoveOutward,
+        InputAction::Confirm,
+        InputAction::Pause,
+        InputAction::Back,
+        InputAction::Mute,
+    ];
+
+    pub const MOVES: [InputAction; 4] = [
+        InputAction::MoveClockwise,
+        InputAction::MoveCounterClockwise,
+        InputAction::MoveInward,
+        InputAction::MoveOutward,
+    ];
+
+    /// Moves that steer around the ring and are applied while held.
+    pub fn is_rotation(self) -> bool {
+        matches!(
+            self,
+            InputAction::MoveClockwise | InputAction::MoveCounterClockwise
+        )
+    }
+}
+
+impl fmt::Display for InputAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            InputAction::MoveClockwise => write!(f, "clockwise"),
+            InputAction::MoveCounterClockwise => write!(f, "counter clockwise"),
+            InputAction::MoveInward => write!(f, "inward"),
+            InputAction::MoveOutward => write!(f, "outward"),
+            InputAction::Confirm => write!(f, "confirm"),
+            InputAction::Pause => write!(f, "pause"),
+            InputAction::Back => write!(f, "back"),
+            InputAction::Mute => write!(f, "mute"),
+        }
+    }
+}
+
+/// Keys bound to every action, loaded from a user-editable file.
+/// Actions missing from the file keep their default keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bindings {
+    pub move_clockwise: Vec<KeyCode>,
+    pub move_counter_clockwise: Vec<KeyCode>,
+    pub move_inward: Vec<KeyCode>,
+    pub move_outward: Vec<KeyCode>,
+    pub confirm: Vec<KeyCode>,
+    pub pause: Vec<KeyCode>,
+    pub back: Vec<KeyCode>,
+    pub mute: Vec<KeyCode>,
+    pub pad: PadBindings,
+}
+
+/// Gamepad buttons bound to every action. The left stick always steers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PadBindings {
+    pub move_clockwise: Vec<Button>,
+    pub move_counter_clockwise: Vec<Button>,
+    pub move_inward: Vec<Button>,
+    pub move_outward: Vec<Button>,
+    pub confirm: Vec<Button>,
+    pub pause: Vec<Button>,
+    pub back: Vec<Button>,
+    pub mute: Vec<Button>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            move_clockwise: vec![KeyCode::H, KeyCode::Left, KeyCode::Numpad4],
+            move_counter_clockwise: vec![KeyCode::L, KeyCode::Right, KeyCode::Numpad6],
+            move_inward: vec![KeyCode::J, KeyCode::Up, KeyCode::Numpad8],
+            move_outward: vec![KeyCode::K, KeyCode::Down, KeyCode::Numpad2],
+            confirm: vec![KeyCode::Return, KeyCode::NumpadEnter],
+            pause: vec![KeyCode::Return, KeyCode::P],
+            back: vec![KeyCode::Escape],
+            mute: vec![KeyCode::F4],
+            pad: PadBindings::default(),
+        }
+    }
+}
+
+impl Default for PadBindings {
+    fn default() -> Self {
+        PadBindings {
+            move_clockwise: vec![Button::DPadLeft],
+            move_counter_clockwise: vec![Button::DPadRight],
+            move_inward: vec![Button::South, Button::DPadUp],
+            move_outward: vec![Button::DPadDown],
+            confirm: vec![Button::South, Button::Start],
+            pause: vec![Button::Start],
+            back: vec![Button::East, Button::Select],
+            mute: vec![],
+        }
+    }
+}
+
+impl PadBindings {
+    pub fn buttons(&self, action: InputAction) -> &[Button] {
+        match action {
+            InputAction::MoveClockwise => &self.move_clockwise,
+            InputAction::MoveCounterClockwise => &self.move_counter_clockwise,
+            InputAction::MoveInward => &self.move_inward,
+            InputAction::MoveOutward => &self.move_outward,
+            InputAction::Confirm => &self.confirm,
+            InputAction::Pause => &self.pause,
+            InputAction::Back => &self.back,
+            InputAction::Mute => &self.mute,
+        }
+    }
+
+    /// Every action `button` is bound to.
+    pub fn actions(&self, button: Button) -> Vec<InputAction> {
+        InputAction::ALL
+            .iter()
+            .copied()
+            .filter(|&action| self.buttons(action).contains(&button))
+            .collect()
+    }
+}
+
+impl Bindings {
+    pub const PATH: &'static str = "/bindings.toml";
+
+    /// Load the bindings file, writing the defaults on first launch so
+    /// there is a file to edit.
+    pub fn load(ctx: &Context) -> Self {
+        if !ctx.fs.exists(Bindings::PATH) {
+            let bindings = Bindings::default();
+            storage::save(ctx, Bindings::PATH, &bindings);
+            return bindings;
+        }
+        storage::load(ctx, Bindings::PATH)
+    }
+
+    pub fn keys(&self, action: InputAction) -> &[KeyCode] {
+        match action {
+            InputAction::MoveClockwise => &self.move_clockwise,
+            InputAction::MoveCounterClockwise => &self.move_counter_clockwise,
+            InputAction::MoveInward => &self.move_inward,
+            InputAction::MoveOutward => &self.move_outward,
+            InputAction::Confirm => &self.confirm,
+            InputAction::Pause => &self.pause,
+            InputAction::Back => &self.back,
+            InputAction::Mute => &self.mute,
+        }
+    }
+
+    fn keys_mut(&mut self, action: InputAction) -> &mut Vec<KeyCode> {
+        match action {
+            InputAction::MoveClockwise => &mut self.move_clockwise,
+            InputAction::MoveCounterClockwise => &mut self.move_counter_clockwise,
+            InputAction::MoveInward => &mut self.move_inward,
+            InputAction::MoveOutward => &mut self.move_outward,
+            InputAction::Confirm => &mut self.confirm,
+            InputAction::Pause => &mut self.pause,
+            InputAction::Back => &mut self.back,
+            InputAction::Mute => &mut self.mute,
+        }
+    }
+
+    /// Bind `action` to `key` alone and return the action the key was taken
+    /// from. That action gets `action`'s old keys if it has none left, so
+    /// Confirm and Back always keep one. Gamepad buttons are kept.
+    pub fn rebind(&mut self, action: InputAction, key: KeyCode) -> Option<InputAction> {
+        let old = std::mem::replace(self.keys_mut(action), vec![key]);
+
+        // Confirm is only read in menus and Pause only in game, so they
+        // may share a key like they do by default
+        let shared = |other| {
+            matches!(
+                (action, other),
+                (InputAction::Confirm, InputAction::Pause)
+                    | (InputAction::Pause, InputAction::Confirm)
+            )
+        };
+        let other = InputAction::ALL
+            .into_iter()
+            .find(|&other| other != action && !shared(other) && self.keys(other).contains(&key))?;
+        let keys = self.keys_mut(other);
+        keys.retain(|&k| k != key);
+        if keys.is_empty() {
+            *keys = old;
+        }
+        Some(other)
+    }
+
+    /// Whether `action` was pressed this tick, on the keyboard or a gamepad.
+    pub fn is(&self, ks: &KeyState, action: InputAction) -> bool {
+        ks.pressed.contains(&action) || ks.key.is_some_and(|key| self.keys(action).contains(&key))
+    }
+
+    /// First of `actions` pressed this tick.
+    pub fn find(&self, ks: &KeyState, actions: &[InputAction]) -> Option<InputAction> {
+        actions.iter().copied().find(|&action| self.is(ks, action))
+    }
+
+    pub fn movement(&self, ks: &KeyState) -> Option<InputAction> {
+        self.find(ks, &InputAction::MOVES)
+    }
+}
+
+/// Key event without a key, handed to scenes for gamepad and mouse
+/// presses. The pressed actions are in `KeyState`.
+pub fn action_input() -> KeyInput {
+    KeyInput {
+        scancode: 0,
+        keycode: None,
+        mods: KeyMods::empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebind_takes_the_key_from_its_old_action() {
+        let mut bindings = Bindings::default();
+        assert_eq!(
+            bindings.rebind(InputAction::Mute, KeyCode::P),
+            Some(InputAction::Pause)
+        );
+        assert_eq!(bindings.keys(InputAction::Mute), [KeyCode::P]);
+        assert_eq!(bindings.keys(InputAction::Pause), [KeyCode::Return]);
+    }
+
+    #[test]
+    fn rebind_keeps_a_key_on_back() {
+        let mut bindings = Bindings::default();
+        assert_eq!(
+            bindings.rebind(InputAction::Mute, KeyCode::Escape),
+            Some(InputAction::Back)
+        );
+        assert_eq!(bindings.keys(InputAction::Back), [KeyCode::F4]);
+    }
+
+    #[test]
+    fn confirm_and_pause_may_share() {
+        let mut bindings = Bindings::default();
+        assert_eq!(
+            bindings.rebind(InputAction::Pause, KeyCode::NumpadEnter),
+            None
+        );
+        assert!(bindings
+            .keys(InputAction::Confirm)
+            .contains(&KeyCode::NumpadEnter));
+    }
+}
@@ -0,0 +1,275 @@
+use ggez::{Context, GameResult};
+use log::warn;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::consts::{GAME_TIME, PLAYER_START_ANGLE, PLAYER_START_LEVEL, WALL_DENSITY};
+use crate::storage;
+use crate::utils::{self, Direction};
+
+pub const LEVELS_DIR: &str = "/levels";
+pub const STORY_PATH: &str = "/levels/story.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RingKind {
+    Enemy,
+    Wall,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rotation {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Movement {
+    pub direction: Rotation,
+    pub speed: f32,
+}
+
+impl Movement {
+    pub fn from_direction(dir: Direction) -> Option<Movement> {
+        match dir {
+            Direction::Left(speed) => Some(Movement {
+                direction: Rotation::Left,
+                speed,
+            }),
+            Direction::Right(speed) => Some(Movement {
+                direction: Rotation::Right,
+                speed,
+            }),
+            Direction::Up | Direction::Down => None,
+        }
+    }
+
+    pub fn to_direction(self) -> Direction {
+        match self.direction {
+            Rotation::Left => Direction::Left(self.speed),
+            Rotation::Right => Direction::Right(self.speed),
+        }
+    }
+}
+
+/// Player spawn point, also used when the player respawns after a hit.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PlayerStart {
+    pub level: i32,
+    pub angle: f32,
+}
+
+impl Default for PlayerStart {
+    fn default() -> Self {
+        PlayerStart {
+            level: PLAYER_START_LEVEL,
+            angle: PLAYER_START_ANGLE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ring {
+    pub level: i32,
+    pub kind: RingKind,
+    /// Arc segments as `(angle, size)` in radians.
+    #[serde(default)]
+    pub segments: Vec<(f32, f32)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub movement: Option<Movement>,
+}
+
+impl Ring {
+    pub fn new(
+        level: i32,
+        kind: RingKind,
+        movement: Option<Movement>,
+        segments: &[(f32, f32)],
+    ) -> Self {
+        Ring {
+            level,
+            kind,
+            segments: segments.to_vec(),
+            movement,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Level {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub intro: String,
+    #[serde(default = "default_time")]
+    pub time: u64,
+    #[serde(default)]
+    pub player: PlayerStart,
+    #[serde(default)]
+    pub rings: Vec<Ring>,
+}
+
+fn default_time() -> u64 {
+    GAME_TIME
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Level {
+            name: String::new(),
+            intro: String::new(),
+            time: GAME_TIME,
+            player: PlayerStart::default(),
+            rings: vec![],
+        }
+    }
+}
+
+impl Level {
+    pub fn endless<R: Rng>(game_level: u32, rng: &mut R) -> Self {
+        let difficulty = Difficulty::for_level(game_level);
+
+        let mut rings = vec![];
+        let mut dir = Direction::Left(difficulty.speed);
+        for level in 1..PLAYER_START_LEVEL {
+            let enemy = difficulty.is_enemy_ring(level);
+            let amount_element = 8 + level as usize + difficulty.extra_slots;
+            let density = if enemy {
+                difficulty.density
+            } else {
+                WALL_DENSITY
+            };
+            let segments = utils::create_map_of_element(rng, amount_element, density);
+            if enemy {
+                rings.push(Ring::new(
+                    level,
+                    RingKind::Enemy,
+                    Movement::from_direction(dir),
+                    &segments,
+                ));
+                dir = Direction::inverse(dir);
+            } else {
+                rings.push(Ring::new(level, RingKind::Wall, None, &segments));
+            }
+        }
+
+        Level {
+            time: difficulty.time,
+            rings,
+            ..Default::default()
+        }
+    }
+
+    pub fn load(ctx: &Context, path: &str) -> GameResult<Level> {
+        storage::read(ctx, path)
+    }
+}
+
+/// Endless mode parameters for a given `GameState::game_level`.
+#[derive(Debug, Clone, Copy)]
+pub struct Difficulty {
+    /// Rings that move and take a life; the rest are walls.
+    pub enemy_rings: i32,
+    pub speed: f32,
+    /// Share of occupied slots on enemy rings.
+    pub density: f32,
+    /// Slots added to every ring; more slots make every gap narrower.
+    pub extra_slots: usize,
+    pub time: u64,
+}
+
+impl Difficulty {
+    /// Level 1 plays like the original endless mode; it gets harder from there.
+    pub fn for_level(game_level: u32) -> Self {
+        let step = game_level.saturating_sub(1);
+
+        Difficulty {
+            enemy_rings: (4 + step as i32 / 4).min(PLAYER_START_LEVEL - 2),
+            speed: (0.01 + 0.001 * step as f32).min(0.025),
+            density: (WALL_DENSITY + 0.02 * step as f32).min(0.7),
+            extra_slots: (step / 3).min(8) as usize,
+            time: GAME_TIME - (step / 2).min(7) as u64,
+        }
+    }
+
+    /// Odd rings are always enemies. Past the first four, even rings turn
+    /// into enemies from the centre outward, so one wall is always left.
+    pub fn is_enemy_ring(&self, level: i32) -> bool {
+        level % 2 != 0 || level / 2 <= self.enemy_rings - 4
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StoryIndex {
+    chapters: Vec<String>,
+}
+
+/// Load the story chapters listed in `story.toml`, leaving `None` in place
+/// of broken files.
+pub fn load_story(ctx: &Context) -> Vec<Option<Level>> {
+    let index = match storage::read::<StoryIndex>(ctx, STORY_PATH) {
+        Ok(index) => index,
+        Err(e) => {
+            warn!("Can't load story: {}", e);
+            return vec![];
+        }
+    };
+
+    index
+        .chapters
+        .iter()
+        .map(
+            |name| match Level::load(ctx, &format!("{}/{}", LEVELS_DIR, name)) {
+                Ok(level) => Some(level),
+                Err(e) => {
+                    warn!("Can't load chapter {}: {}", name, e);
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_one_is_the_original_endless() {
+        let d = Difficulty::for_level(1);
+        assert_eq!(d.enemy_rings, 4);
+        assert_eq!(d.speed, 0.01);
+        assert_eq!(d.density, WALL_DENSITY);
+        assert_eq!(d.extra_slots, 0);
+        assert_eq!(d.time, GAME_TIME);
+        let enemies: Vec<i32> = (1..PLAYER_START_LEVEL)
+            .filter(|&level| d.is_enemy_ring(level))
+            .collect();
+        assert_eq!(enemies, vec![1, 3, 5, 7]);
+    }
+
+    #[test]
+    fn difficulty_only_gets_harder_and_stays_clamped() {
+        let mut prev = Difficulty::for_level(1);
+        for game_level in 2..=200 {
+            let d = Difficulty::for_level(game_level);
+            assert!(d.enemy_rings >= prev.enemy_rings);
+            assert!(d.speed >= prev.speed);
+            assert!(d.density >= prev.density);
+            assert!(d.extra_slots >= prev.extra_slots);
+            assert!(d.time <= prev.time);
+
+            assert!(d.enemy_rings <= PLAYER_START_LEVEL - 2);
+            assert!(d.speed <= 0.025);
+            assert!(d.density <= 0.7);
+            assert!(d.extra_slots <= 8);
+            assert!(d.time >= GAME_TIME - 7);
+            let walls = (1..PLAYER_START_LEVEL)
+                .filter(|&level| !d.is_enemy_ring(level))
+                .count();
+            assert_eq!(walls as i32, PLAYER_START_LEVEL - 1 - d.enemy_rings);
+            prev = d;
+        }
+    }
+}
@@ -4,40 +4,89 @@ extern crate specs;
 #[macro_use]
 extern crate specs_derive;
 
+mod audio;
 mod consts;
 mod ecs;
+mod input;
+mod levels;
+mod mixer;
+mod replay;
 mod scenes;
 mod shapes;
+mod sim;
+mod soundtrack;
+mod storage;
+mod themes;
 mod utils;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use winit::dpi::LogicalSize;
 
 use std::env;
+use std::process;
 
 use ecs::resources::{GameState, KeyState};
-use ggez::audio;
+use ggez::event::{Axis, Button, MouseButton};
 use ggez::graphics;
+use ggez::input::gamepad::GamepadId;
 use ggez::input::keyboard::KeyInput;
 use ggez::{conf, event, Context, GameResult};
 use log::info;
+use rodio::OutputStream;
 use scenes::{menu::MenuScene, stack::SceneStack};
 use specs::prelude::*;
 
-use crate::ecs::resources::Sound;
+use crate::audio::Clip;
+use crate::ecs::resources::{HighScores, Progress, Recorder, Screen, Seed, Settings, Sound, Story};
+use crate::input::{Bindings, InputAction};
 use crate::utils::Colour;
 
+#[derive(Debug, Default)]
+struct Args {
+    seed: Option<u64>,
+    headless: Option<PathBuf>,
+    level: Option<PathBuf>,
+}
+
+impl Args {
+    fn parse() -> Result<Self, String> {
+        let mut result = Args::default();
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let value = args.next().unwrap_or_default();
+                    let seed = value
+                        .parse()
+                        .map_err(|_| format!("Bad seed \"{}\", expected a number", value))?;
+                    result.seed = Some(seed);
+                }
+                "--headless" => result.headless = args.next().map(PathBuf::from),
+                "--level" => result.level = args.next().map(PathBuf::from),
+                _ => {}
+            }
+        }
+        Ok(result)
+    }
+}
+
 struct MainState {
     world: World,
     scenes: SceneStack,
+    /// Sound stops when the device stream is dropped.
+    _audio: Option<OutputStream>,
 }
 
 impl MainState {
-    pub fn new(ctx: &mut Context) -> GameResult<MainState> {
-        let wall_sound = audio::Source::new(ctx, "/sounds/knock.flac").unwrap();
-        let enemy_sound = audio::Source::new(ctx, "/sounds/enemy.flac").unwrap();
+    pub fn new(ctx: &mut Context, args: &Args) -> GameResult<MainState> {
+        let (stream, output) = audio::open().unzip();
         let sound = Sound {
-            wall: Some(wall_sound),
-            enemy: Some(enemy_sound),
+            output: output.clone(),
+            wall: output
+                .as_ref()
+                .and_then(|_| Clip::load(ctx, "/sounds/knock.flac")),
+            enemy: output
+                .as_ref()
+                .and_then(|_| Clip::load(ctx, "/sounds/enemy.flac")),
         };
 
         ctx.gfx.add_font(
@@ -46,31 +95,97 @@ impl MainState {
         );
 
         let mut world = specs::World::new();
-        world.register::<ecs::components::Position>();
-        world.register::<ecs::components::View>();
-        world.register::<ecs::components::Player>();
-        world.register::<ecs::components::Enemy>();
-        world.register::<ecs::components::ConstantMovement>();
+        sim::register(&mut world);
 
-        world.insert(GameState::default());
+        let settings = storage::load::<Settings>(ctx, Settings::PATH);
+        ctx.gfx.set_fullscreen(settings.fullscreen_type())?;
+        let themes = themes::Themes::load(ctx);
+        let mut gs = GameState::default();
+        settings.apply(&mut gs, &themes);
+        world.insert(gs);
+        world.insert(settings);
+        world.insert(themes);
         world.insert(KeyState::default());
+        let (width, height) = ctx.gfx.drawable_size();
+        world.insert(Screen { width, height });
+        world.insert(args.seed.map(Seed::new).unwrap_or_default());
         world.insert(sound);
+        world.insert(soundtrack::Soundtrack::load(ctx, output));
+        world.insert(Recorder::default());
+        world.insert(storage::load::<Progress>(ctx, Progress::PATH));
+        world.insert(storage::load::<HighScores>(ctx, HighScores::PATH));
+        world.insert(Bindings::load(ctx));
+        world.insert(Story {
+            chapters: levels::load_story(ctx),
+        });
+
+        let menu = Box::new(MenuScene::new(ctx, &mut world));
+        let scenes = SceneStack::new(ctx, &mut world, menu);
 
-        let scenes = SceneStack::new(Box::new(MenuScene::new(ctx, &mut world)));
+        Ok(MainState {
+            world,
+            scenes,
+            _audio: stream,
+        })
+    }
 
-        Ok(MainState { world, scenes })
+    /// Actions handled the same way in every scene, on a fresh press and
+    /// not while a scene is taking keys for a binding.
+    fn global_actions(&mut self) {
+        if self.scenes.captures_keys() {
+            return;
+        }
+        let ks = self.world.fetch::<KeyState>();
+        if self.world.fetch::<Bindings>().is(&ks, InputAction::Mute) {
+            let mut settings = self.world.fetch_mut::<Settings>();
+            settings.mixer.muted = !settings.mixer.muted;
+            settings.changed = true;
+        }
+    }
+
+    /// Turn the left stick position into a held rotation.
+    fn steer(&mut self, value: f32) {
+        let mut k = self.world.fetch_mut::<KeyState>();
+        let deflection = (value.abs() - consts::STICK_DEADZONE) / (1.0 - consts::STICK_DEADZONE);
+        // Kept in hundredths so replays reproduce the same speed
+        let stick = (deflection.min(1.0) * 100.0).round() / 100.0;
+        if stick > 0.0 {
+            // Pushing left steers like the left key
+            k.pad_move = Some(if value < 0.0 {
+                InputAction::MoveClockwise
+            } else {
+                InputAction::MoveCounterClockwise
+            });
+            k.stick = stick;
+        } else if k.stick > 0.0 {
+            k.pad_move = None;
+            k.stick = 0.0;
+        }
     }
 }
 
 impl event::EventHandler<ggez::GameError> for MainState {
     fn update(&mut self, ctx: &mut Context) -> GameResult {
-        while ctx.time.check_update_time(consts::DESIRED_FPS) {
+        let updates = self.world.fetch::<Settings>().updates_per_second();
+        while ctx.time.check_update_time(updates) {
             self.scenes.update(ctx, &mut self.world)?;
             // Reset input
-            let mut k = self.world.fetch_mut::<KeyState>();
-            k.key = None;
-            k.mods = None;
+            self.world.fetch_mut::<KeyState>().next_tick();
+
+            let mut settings = self.world.fetch_mut::<Settings>();
+            if settings.changed {
+                settings.changed = false;
+                if settings.fullscreen != ctx.gfx.window().fullscreen().is_some() {
+                    ctx.gfx.set_fullscreen(settings.fullscreen_type())?;
+                }
+                storage::save(ctx, Settings::PATH, &*settings);
+            }
         }
+
+        let dt = ctx.time.delta().as_secs_f32();
+        self.world
+            .fetch_mut::<soundtrack::Soundtrack>()
+            .update(dt, &self.world.fetch::<Settings>().mixer);
         Ok(())
     }
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
@@ -78,22 +193,168 @@ impl event::EventHandler<ggez::GameError> for MainState {
             ctx,
             Colour::Bg.value(&self.world.fetch::<GameState>().theme),
         );
+        canvas.set_screen_coordinates(self.world.fetch::<Screen>().rect());
         self.scenes.draw(ctx, &mut self.world, &mut canvas)?;
         canvas.finish(ctx)
     }
 
     fn key_down_event(&mut self, ctx: &mut Context, input: KeyInput, repeat: bool) -> GameResult {
-        {
+        let fresh = {
             let mut k = self.world.fetch_mut::<KeyState>();
             k.key = input.keycode;
             k.mods = Some(input.mods);
-            k.repeat = repeat;
+            k.pressed.clear();
+            // Key repeat only re-sends keys that are already held
+            let fresh = input.keycode.is_some_and(|key| k.held.insert(key));
+            if fresh {
+                k.just_pressed.extend(input.keycode);
+            }
+            fresh
+        };
+        if fresh {
+            self.global_actions();
         }
 
         self.scenes
             .key_down_event(ctx, input, repeat, &mut self.world);
         Ok(())
     }
+
+    fn mouse_motion_event(
+        &mut self,
+        ctx: &mut Context,
+        x: f32,
+        y: f32,
+        _dx: f32,
+        _dy: f32,
+    ) -> GameResult {
+        let (x, y) = self.world.fetch::<Screen>().screen_point(x, y);
+        self.scenes.mouse_motion_event(ctx, x, y, &mut self.world);
+        Ok(())
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut Context,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) -> GameResult {
+        let (x, y) = self.world.fetch::<Screen>().screen_point(x, y);
+        self.scenes
+            .mouse_button_down_event(ctx, button, x, y, &mut self.world);
+        Ok(())
+    }
+
+    fn gamepad_button_down_event(
+        &mut self,
+        ctx: &mut Context,
+        btn: Button,
+        _id: GamepadId,
+    ) -> GameResult {
+        let actions = self.world.fetch::<Bindings>().pad.actions(btn);
+        {
+            let mut k = self.world.fetch_mut::<KeyState>();
+            if let Some(&action) = actions.iter().find(|a| a.is_rotation()) {
+                k.pad_move = Some(action);
+                k.stick = 0.0;
+            }
+            k.press(actions);
+        }
+        self.global_actions();
+
+        self.scenes
+            .gamepad_button_down_event(ctx, btn, &mut self.world);
+        Ok(())
+    }
+
+    fn gamepad_button_up_event(
+        &mut self,
+        ctx: &mut Context,
+        btn: Button,
+        _id: GamepadId,
+    ) -> GameResult {
+        {
+            let actions = self.world.fetch::<Bindings>().pad.actions(btn);
+            let mut k = self.world.fetch_mut::<KeyState>();
+            if k.stick == 0.0 && k.pad_move.is_some_and(|a| actions.contains(&a)) {
+                k.pad_move = None;
+            }
+        }
+
+        self.scenes
+            .gamepad_button_up_event(ctx, btn, &mut self.world);
+        Ok(())
+    }
+
+    fn gamepad_axis_event(
+        &mut self,
+        ctx: &mut Context,
+        axis: Axis,
+        value: f32,
+        _id: GamepadId,
+    ) -> GameResult {
+        if axis == Axis::LeftStickX {
+            self.steer(value);
+        }
+
+        self.scenes
+            .gamepad_axis_event(ctx, axis, value, &mut self.world);
+        Ok(())
+    }
+
+    fn key_up_event(&mut self, ctx: &mut Context, input: KeyInput) -> GameResult {
+        if let Some(key) = input.keycode {
+            let mut k = self.world.fetch_mut::<KeyState>();
+            k.held.remove(&key);
+            k.just_released.insert(key);
+        }
+
+        self.scenes.key_up_event(ctx, input, &mut self.world);
+        Ok(())
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        ctx: &mut Context,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) -> GameResult {
+        let (x, y) = self.world.fetch::<Screen>().screen_point(x, y);
+        self.scenes
+            .mouse_button_up_event(ctx, button, x, y, &mut self.world);
+        Ok(())
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, x: f32, y: f32) -> GameResult {
+        self.scenes.mouse_wheel_event(ctx, x, y, &mut self.world);
+        Ok(())
+    }
+
+    fn focus_event(&mut self, ctx: &mut Context, gained: bool) -> GameResult {
+        // Keys let go while the window is in the background never send key up
+        if !gained {
+            self.world.fetch_mut::<KeyState>().release_all();
+        }
+
+        self.scenes.focus_event(ctx, gained, &mut self.world);
+        Ok(())
+    }
+
+    fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        // Also sent when the window moves to a display with another DPI
+        *self.world.fetch_mut::<Screen>() = Screen { width, height };
+        self.scenes
+            .resize_event(ctx, width, height, &mut self.world);
+        Ok(())
+    }
+
+    fn text_input_event(&mut self, ctx: &mut Context, character: char) -> GameResult {
+        self.scenes
+            .text_input_event(ctx, character, &mut self.world);
+        Ok(())
+    }
 }
 
 fn main() -> GameResult {
@@ -108,6 +369,15 @@ fn main() -> GameResult {
             .init();
     }
 
+    let args = Args::parse().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(2);
+    });
+
+    if let Some(path) = &args.headless {
+        return run_headless(path, &args);
+    }
+
     // Find resource
     let mut current_dir = env::current_dir()?;
     current_dir.push("resources");
@@ -117,9 +387,8 @@ fn main() -> GameResult {
         .with_conf_file(false)
         .window_setup(conf::WindowSetup::default().title("ToCenter"))
         .window_mode(conf::WindowMode {
-            resizable: false,
-            borderless: true,
-            logical_size: Some(LogicalSize::new(500.0, 500.0)),
+            resizable: true,
+            logical_size: Some(LogicalSize::new(consts::WINDOW_SIZE, consts::WINDOW_SIZE)),
             ..Default::default()
         });
 
@@ -138,7 +407,24 @@ fn main() -> GameResult {
     let (mut ctx, event_loop) = cb.build()?;
     ctx.gfx.set_window_icon(&ctx, Path::new("/128x128.png"))?;
 
-    let state = MainState::new(&mut ctx)?;
+    let state = MainState::new(&mut ctx, &args)?;
     // Run
     event::run(ctx, event_loop, state)
 }
+
+fn run_headless(script: &Path, args: &Args) -> GameResult {
+    let script = sim::Script::load(script)?;
+    let level = match &args.level {
+        Some(path) => Some(sim::load_level(path)?),
+        None => None,
+    };
+    let seed = args.seed.map(Seed::new).unwrap_or_default();
+    info!("Headless run with seed {}", seed.value);
+
+    let outcome = sim::Headless::new(seed, level).run(&script);
+    println!("{}", outcome);
+    if outcome.status == Some(utils::GameStatus::GameOver) {
+        process::exit(1);
+    }
+    Ok(())
+}
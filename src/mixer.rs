@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+/// Group of sounds sharing a volume.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bus {
+    Music,
+    Sfx,
+}
+
+/// Volume of every bus, each scaled by `master`. Every sound is played
+/// at the volume the mixer gives for its bus.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Mixer {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+    pub muted: bool,
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Mixer {
+            master: 1.0,
+            music: 0.7,
+            sfx: 1.0,
+            muted: false,
+        }
+    }
+}
+
+impl Mixer {
+    pub fn volume(&self, bus: Bus) -> f32 {
+        if self.muted {
+            return 0.0;
+        }
+        let volume = match bus {
+            Bus::Music => self.music,
+            Bus::Sfx => self.sfx,
+        };
+        self.master * volume
+    }
+}
@@ -0,0 +1,365 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use ggez::{GameError, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::consts::{AIM_PRECISION, REPLAY_MAX_RUN};
+use crate::ecs::resources::KeyState;
+use crate::input::{Bindings, InputAction};
+use crate::utils::{Control, Mode};
+
+pub const REPLAYS_DIR: &str = "/replays";
+pub const LAST_PATH: &str = "/replays/last.toml";
+
+/// Input seen by `UpdatePosition` on one tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Tick {
+    /// Turn around the ring.
+    pub rotation: Option<InputAction>,
+    /// Move between rings, possibly while turning.
+    pub step: Option<InputAction>,
+    /// The turn, or the step without one, was already held last tick.
+    pub repeat: bool,
+    /// Left stick deflection in percent, 0 for keys and the d-pad.
+    pub stick: u8,
+    /// Cursor angle in thousandths of a radian, for mouse control.
+    pub aim: Option<u16>,
+}
+
+impl Tick {
+    /// Keep only what moves the player, so replays don't depend on bindings.
+    /// Holding both ways of a turn or step cancels it out.
+    pub fn from_key_state(control: &Control, ks: &KeyState, bindings: &Bindings) -> Self {
+        let held = |a, b| match (ks.holds(bindings, a), ks.holds(bindings, b)) {
+            (true, false) => Some(a),
+            (false, true) => Some(b),
+            _ => None,
+        };
+        let rotation = held(
+            InputAction::MoveClockwise,
+            InputAction::MoveCounterClockwise,
+        )
+        .or(ks.pad_move);
+        let step = held(InputAction::MoveInward, InputAction::MoveOutward);
+        let repeat = rotation
+            .or(step)
+            .is_some_and(|action| !ks.started(bindings, action));
+
+        let aim = match control {
+            Control::Mouse => ks.aim.map(|aim| (aim * AIM_PRECISION).round() as u16),
+            _ => None,
+        };
+        Tick {
+            rotation,
+            step,
+            repeat,
+            stick: (ks.stick * 100.0).round() as u8,
+            aim,
+        }
+    }
+
+    /// The turn, or the step when there is no turn.
+    pub fn action(self) -> Option<InputAction> {
+        self.rotation.or(self.step)
+    }
+
+    pub fn stick(self) -> f32 {
+        f32::from(self.stick) / 100.0
+    }
+
+    pub fn aim(self) -> Option<f32> {
+        self.aim.map(|aim| f32::from(aim) / AIM_PRECISION)
+    }
+
+    fn action_char(action: Option<InputAction>) -> char {
+        match action {
+            Some(InputAction::MoveClockwise) => 'l',
+            Some(InputAction::MoveCounterClockwise) => 'r',
+            Some(InputAction::MoveInward) => 'u',
+            Some(InputAction::MoveOutward) => 'd',
+            _ => 'n',
+        }
+    }
+
+    fn char_action(c: char) -> Option<Option<InputAction>> {
+        match c.to_ascii_lowercase() {
+            'l' => Some(Some(InputAction::MoveClockwise)),
+            'r' => Some(Some(InputAction::MoveCounterClockwise)),
+            'u' => Some(Some(InputAction::MoveInward)),
+            'd' => Some(Some(InputAction::MoveOutward)),
+            'n' => Some(None),
+            _ => None,
+        }
+    }
+
+    fn to_char(self) -> char {
+        let c = Tick::action_char(self.action());
+        if self.repeat {
+            c.to_ascii_uppercase()
+        } else {
+            c
+        }
+    }
+
+    fn from_char(c: char) -> Option<Self> {
+        let action = Tick::char_action(c)?;
+        let is_rotation = action.is_some_and(InputAction::is_rotation);
+        Some(Tick {
+            rotation: action.filter(|_| is_rotation),
+            step: action.filter(|_| !is_rotation),
+            repeat: c.is_ascii_uppercase(),
+            stick: 0,
+            aim: None,
+        })
+    }
+}
+
+/// Ticks played with the same control scheme. `input` is run-length
+/// encoded, e.g. `40n3l12L4L+u5L@80;2n#1571;`: a count (1 when omitted) and
+/// a key, where `n` is no key and upper case marks a repeated key press,
+/// optionally followed by `+` and a step taken while turning, by `@` and the
+/// stick deflection in percent and by `#` and the mouse aim in thousandths
+/// of a radian. A `;` ends the run after a stick or aim value, so its digits
+/// don't run into the next count.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    pub control: Control,
+    pub input: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: String,
+    pub seed: u64,
+    pub game_level: u32,
+    #[serde(default)]
+    pub score: u64,
+    #[serde(default)]
+    pub segments: Vec<Segment>,
+    pub mode: Mode,
+}
+
+impl Replay {
+    pub fn new(mode: Mode, game_level: u32, seed: u64) -> Self {
+        Replay {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            seed,
+            game_level,
+            score: 0,
+            segments: vec![],
+            mode,
+        }
+    }
+
+    pub fn is_current_version(&self) -> bool {
+        self.version == env!("CARGO_PKG_VERSION")
+    }
+
+    pub fn encode(&mut self, ticks: &[(Control, Tick)]) {
+        self.segments.clear();
+
+        let mut i = 0;
+        while i < ticks.len() {
+            let control = ticks[i].0.clone();
+            let mut input = String::new();
+            while i < ticks.len() && ticks[i].0 == control {
+                let tick = ticks[i].1;
+                let mut count = 0;
+                while i < ticks.len() && ticks[i].0 == control && ticks[i].1 == tick {
+                    count += 1;
+                    i += 1;
+                }
+                if count > 1 {
+                    input.push_str(&count.to_string());
+                }
+                input.push(tick.to_char());
+                if let (Some(_), Some(step)) = (tick.rotation, tick.step) {
+                    input.push('+');
+                    input.push(Tick::action_char(Some(step)));
+                }
+                if tick.stick > 0 {
+                    input.push_str(&format!("@{}", tick.stick));
+                }
+                if let Some(aim) = tick.aim {
+                    input.push_str(&format!("#{}", aim));
+                }
+                if tick.stick > 0 || tick.aim.is_some() {
+                    input.push(';');
+                }
+            }
+            self.segments.push(Segment { control, input });
+        }
+    }
+
+    pub fn decode(&self) -> GameResult<Vec<(Control, Tick)>> {
+        let mut ticks = vec![];
+        for segment in &self.segments {
+            let mut chars = segment.input.chars().peekable();
+            while chars.peek().is_some() {
+                let count = Replay::number(&mut chars);
+                let c = chars.next().unwrap_or_default();
+                let mut tick = Tick::from_char(c)
+                    .ok_or_else(|| GameError::CustomError(format!("Bad replay input: {}", c)))?;
+                if chars.next_if_eq(&'+').is_some() {
+                    let c = chars.next().unwrap_or_default();
+                    let step = Tick::char_action(c)
+                        .flatten()
+                        .filter(|action| !action.is_rotation())
+                        .ok_or_else(|| GameError::CustomError(format!("Bad replay step: {}", c)))?;
+                    tick.step = Some(step);
+                }
+                if chars.next_if_eq(&'@').is_some() {
+                    tick.stick = Replay::number(&mut chars).parse().map_err(|_| {
+                        GameError::CustomError(format!("Bad replay stick in: {}", segment.input))
+                    })?;
+                }
+                if chars.next_if_eq(&'#').is_some() {
+                    let aim = Replay::number(&mut chars).parse().map_err(|_| {
+                        GameError::CustomError(format!("Bad replay aim in: {}", segment.input))
+                    })?;
+                    tick.aim = Some(aim);
+                }
+                if (tick.stick > 0 || tick.aim.is_some()) && chars.next_if_eq(&';').is_none() {
+                    return Err(GameError::CustomError(format!(
+                        "Missing ; in replay: {}",
+                        segment.input
+                    )));
+                }
+
+                let n = if count.is_empty() {
+                    1
+                } else {
+                    count
+                        .parse()
+                        .ok()
+                        .filter(|&n| n <= REPLAY_MAX_RUN)
+                        .ok_or_else(|| {
+                            GameError::CustomError(format!("Bad replay count: {}", count))
+                        })?
+                };
+                ticks.extend((0..n).map(|_| (segment.control.clone(), tick)));
+            }
+        }
+        Ok(ticks)
+    }
+
+    fn number(chars: &mut Peekable<Chars>) -> String {
+        let mut number = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+            number.push(c);
+        }
+        number
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(action: Option<InputAction>, stick: u8, aim: Option<u16>) -> Tick {
+        let is_rotation = action.is_some_and(InputAction::is_rotation);
+        Tick {
+            rotation: action.filter(|_| is_rotation),
+            step: action.filter(|_| !is_rotation),
+            repeat: false,
+            stick,
+            aim,
+        }
+    }
+
+    fn round_trip(ticks: &[(Control, Tick)]) -> String {
+        let mut replay = Replay::new(Mode::Endless, 1, 0);
+        replay.encode(ticks);
+        assert_eq!(replay.decode().unwrap(), ticks);
+        replay.segments[0].input.clone()
+    }
+
+    #[test]
+    fn stick_followed_by_run() {
+        let mut ticks = vec![(
+            Control::Normal,
+            tick(Some(InputAction::MoveClockwise), 80, None),
+        )];
+        ticks.extend((0..12).map(|_| (Control::Normal, tick(None, 0, None))));
+        assert_eq!(round_trip(&ticks), "l@80;12n");
+    }
+
+    #[test]
+    fn stick_runs() {
+        let left = |stick| {
+            (
+                Control::Normal,
+                tick(Some(InputAction::MoveClockwise), stick, None),
+            )
+        };
+        let mut ticks = vec![left(5); 3];
+        ticks.extend(vec![left(100); 40]);
+        ticks.push(left(7));
+        ticks.push((
+            Control::Normal,
+            tick(Some(InputAction::MoveInward), 0, None),
+        ));
+        round_trip(&ticks);
+    }
+
+    #[test]
+    fn aim_followed_by_runs() {
+        let aim = |aim| (Control::Mouse, tick(None, 0, Some(aim)));
+        let mut ticks = vec![aim(1234)];
+        ticks.extend(vec![aim(2000); 5]);
+        assert_eq!(round_trip(&ticks), "n#1234;5n#2000;");
+    }
+
+    #[test]
+    fn aim_with_stick_and_step() {
+        let mut turn = tick(Some(InputAction::MoveCounterClockwise), 35, Some(6283));
+        turn.step = Some(InputAction::MoveInward);
+        let mut ticks = vec![(Control::Mouse, turn); 3];
+        ticks.push((Control::Mouse, tick(None, 0, Some(0))));
+        ticks.extend(vec![(Control::Mouse, tick(None, 0, None)); 10]);
+        assert_eq!(round_trip(&ticks), "3r+u@35#6283;n#0;10n");
+    }
+
+    #[test]
+    fn missing_terminator() {
+        let mut replay = Replay::new(Mode::Endless, 1, 0);
+        replay.segments.push(Segment {
+            control: Control::Normal,
+            input: "l@8012n".to_string(),
+        });
+        assert!(replay.decode().is_err());
+    }
+
+    #[test]
+    fn run_count_is_capped() {
+        let mut replay = Replay::new(Mode::Endless, 1, 0);
+        replay.segments.push(Segment {
+            control: Control::Normal,
+            input: format!("{}n", REPLAY_MAX_RUN),
+        });
+        assert_eq!(replay.decode().unwrap().len(), REPLAY_MAX_RUN);
+
+        replay.segments[0].input = "99999999999n".to_string();
+        assert!(replay.decode().is_err());
+    }
+
+    #[test]
+    fn replay_file_round_trip() {
+        let mut replay = Replay::new(Mode::Story(2), 3, 42);
+        replay.score = 120;
+        replay.segments = vec![
+            Segment {
+                control: Control::Normal,
+                input: "40n3l12L".to_string(),
+            },
+            Segment {
+                control: Control::Mouse,
+                input: "5n#1234;".to_string(),
+            },
+        ];
+
+        let data = toml::to_string(&replay).unwrap();
+        assert_eq!(toml::from_str::<Replay>(&data).unwrap(), replay);
+    }
+}
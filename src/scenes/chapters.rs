@@ -0,0 +1,103 @@
+use std::fmt;
+
+use ggez::graphics::Canvas;
+use ggez::input::keyboard::KeyInput;
+use ggez::{Context, GameResult};
+use specs::prelude::*;
+
+use crate::ecs::resources::{Action, KeyState, Menu, Progress, Story};
+use crate::ecs::systems::{MenuRender, UpdateGlobalState, UpdateMenu};
+use crate::input::{Bindings, InputAction};
+use crate::scenes::intro::IntroScene;
+use crate::scenes::stack::{Scene, SceneResult, Transition};
+
+pub struct ChapterScene<'a, 'b> {
+    dispatcher: Dispatcher<'a, 'b>,
+    menu: Option<Menu>,
+}
+
+impl<'a, 'b> ChapterScene<'a, 'b> {
+    pub fn new(_ctx: &mut Context, world: &mut World) -> Self {
+        let unlocked = world.fetch::<Progress>().unlocked;
+
+        let mut menu = Menu::new("story mode".to_string());
+        for (i, chapter) in world.fetch::<Story>().chapters.iter().enumerate() {
+            let name = chapter.as_ref().map_or("missing", |chapter| &chapter.name);
+            menu.add_item(
+                Action::Chapter(i),
+                format!("{}. {}", i + 1, name),
+                10.0,
+                i < unlocked && chapter.is_some(),
+            );
+        }
+        menu.add_item(Action::Back, "back".to_string(), 60.0, true);
+
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(UpdateGlobalState, "global_system", &[])
+            .with(UpdateMenu, "menu_system", &[])
+            .build();
+        dispatcher.setup(world);
+        Self {
+            dispatcher,
+            menu: Some(menu),
+        }
+    }
+}
+
+impl<'a, 'b> Scene for ChapterScene<'a, 'b> {
+    fn update(&mut self, _ctx: &mut Context, world: &mut World) -> SceneResult {
+        self.dispatcher.dispatch(world);
+
+        Ok(Transition::None)
+    }
+
+    fn draw(&mut self, _ctx: &mut Context, world: &mut World, canvas: &mut Canvas) -> GameResult {
+        let mut render = MenuRender::new(canvas);
+        render.run_now(world);
+        Ok(())
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        _input: KeyInput,
+        _repeat: bool,
+        world: &mut World,
+    ) -> SceneResult {
+        let pressed = world.fetch::<Bindings>().find(
+            &world.fetch::<KeyState>(),
+            &[InputAction::Confirm, InputAction::Back],
+        );
+        match pressed {
+            Some(InputAction::Confirm) => {
+                if !world.fetch::<Menu>().is_current_available() {
+                    return Ok(Transition::None);
+                }
+                let action = world.fetch::<Menu>().get_currect_action();
+                match action {
+                    Action::Chapter(chapter) => Ok(Transition::Replace(Box::new(IntroScene::new(
+                        ctx, world, chapter, true,
+                    )))),
+                    Action::Back => Ok(Transition::Pop),
+                    _ => Ok(Transition::None),
+                }
+            }
+            Some(InputAction::Back) => Ok(Transition::Pop),
+            _ => Ok(Transition::None),
+        }
+    }
+
+    fn menu(&mut self) -> Option<&mut Option<Menu>> {
+        Some(&mut self.menu)
+    }
+
+    fn name(&self) -> &str {
+        "Chapter"
+    }
+}
+
+impl<'a, 'b> fmt::Debug for ChapterScene<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
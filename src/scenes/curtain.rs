@@ -8,8 +8,9 @@ use specs::{Dispatcher, DispatcherBuilder, World};
 
 use crate::ecs::resources::Curtain;
 use crate::ecs::systems::{CurtainRender, UpdateCurtain};
+use crate::levels::PlayerStart;
 use crate::scenes::game::GameScene;
-use crate::scenes::stack::{Scene, Transition};
+use crate::scenes::stack::{Scene, SceneResult, Transition};
 
 pub struct CurtainScene<'a, 'b> {
     dispatcher: Dispatcher<'a, 'b>,
@@ -18,7 +19,7 @@ pub struct CurtainScene<'a, 'b> {
 impl<'a, 'b> CurtainScene<'a, 'b> {
     pub fn new(world: &mut World, on_player: bool) -> Self {
         let res = if on_player {
-            Curtain::new_center_player()
+            Curtain::new_center_player(&world.fetch::<PlayerStart>())
         } else {
             Curtain::new_center()
         };
@@ -38,7 +39,7 @@ impl<'a, 'b> Scene for CurtainScene<'a, 'b> {
         true
     }
 
-    fn update(&mut self, ctx: &mut Context, world: &mut World) -> Result<Transition, String> {
+    fn update(&mut self, ctx: &mut Context, world: &mut World) -> SceneResult {
         self.dispatcher.dispatch(world);
         if world.fetch::<Curtain>().radius > 500.0 {
             return Ok(Transition::Pop);
@@ -67,7 +68,7 @@ impl<'a, 'b> Scene for CurtainScene<'a, 'b> {
         _input: KeyInput,
         _repeat: bool,
         _world: &mut World,
-    ) -> Result<Transition, String> {
+    ) -> SceneResult {
         Ok(Transition::None)
     }
 
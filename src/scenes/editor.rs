@@ -0,0 +1,113 @@
+use std::fmt;
+
+use ggez::graphics::Canvas;
+use ggez::input::keyboard::KeyCode;
+use ggez::input::keyboard::KeyInput;
+use ggez::{Context, GameResult};
+use specs::prelude::*;
+
+use crate::ecs::resources::{Editor, GameState, KeyState};
+use crate::ecs::systems::{EditorRender, UpdateEditor, UpdateGlobalState};
+use crate::input::{Bindings, InputAction};
+use crate::levels::{Level, LEVELS_DIR};
+use crate::scenes::curtain::CurtainScene;
+use crate::scenes::game::GameScene;
+use crate::scenes::stack::{Scene, SceneResult, Transition};
+use crate::storage;
+use crate::utils::Mode;
+
+pub struct EditorScene<'a, 'b> {
+    dispatcher: Dispatcher<'a, 'b>,
+}
+
+impl<'a, 'b> EditorScene<'a, 'b> {
+    pub fn new(ctx: &mut Context, world: &mut World) -> Self {
+        let mut editor = Editor::default();
+        if ctx.fs.exists(Editor::PATH) {
+            match Level::load(ctx, Editor::PATH) {
+                Ok(level) => editor.level = level,
+                Err(e) => editor.message = format!("can't load level: {}", e),
+            }
+        }
+        world.insert(editor);
+
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(UpdateGlobalState, "global_system", &[])
+            .with(UpdateEditor, "editor_system", &[])
+            .build();
+        dispatcher.setup(world);
+        Self { dispatcher }
+    }
+
+    fn save(ctx: &mut Context, world: &mut World) {
+        let mut editor = world.fetch_mut::<Editor>();
+        editor.level.rings.retain(|r| !r.segments.is_empty());
+        editor.level.rings.sort_by_key(|r| r.level);
+        let result = ctx
+            .fs
+            .create_dir(LEVELS_DIR)
+            .and_then(|_| storage::write(ctx, Editor::PATH, &editor.level));
+        editor.message = match result {
+            Ok(_) => format!("saved to {}", Editor::PATH),
+            Err(e) => format!("can't save level: {}", e),
+        };
+    }
+}
+
+impl<'a, 'b> Scene for EditorScene<'a, 'b> {
+    fn update(&mut self, _ctx: &mut Context, world: &mut World) -> SceneResult {
+        self.dispatcher.dispatch(world);
+
+        Ok(Transition::None)
+    }
+
+    fn draw(&mut self, ctx: &mut Context, world: &mut World, canvas: &mut Canvas) -> GameResult {
+        let mut render = EditorRender::new(ctx, canvas);
+        render.run_now(world);
+        Ok(())
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        input: KeyInput,
+        _repeat: bool,
+        world: &mut World,
+    ) -> SceneResult {
+        if world
+            .fetch::<Bindings>()
+            .is(&world.fetch::<KeyState>(), InputAction::Back)
+        {
+            return Ok(Transition::Pop);
+        }
+
+        match input.keycode {
+            Some(KeyCode::S) => {
+                EditorScene::save(ctx, world);
+                Ok(Transition::None)
+            }
+            Some(KeyCode::T) | Some(KeyCode::F5) => {
+                world.fetch_mut::<Editor>().message.clear();
+                world.fetch_mut::<GameState>().mode = Mode::Test;
+                Ok(Transition::MultiReplace(
+                    vec![
+                        Box::new(GameScene::new(ctx, world)),
+                        Box::new(CurtainScene::new(world, true)),
+                    ],
+                    0,
+                ))
+            }
+            _ => Ok(Transition::None),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "Editor"
+    }
+}
+
+impl<'a, 'b> fmt::Debug for EditorScene<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -0,0 +1,91 @@
+use std::fmt;
+
+use ggez::graphics::Canvas;
+use ggez::input::keyboard::KeyInput;
+use ggez::{Context, GameResult};
+use specs::prelude::*;
+
+use crate::ecs::resources::{Action, KeyState, Menu};
+use crate::ecs::systems::{MenuRender, UpdateGlobalState, UpdateMenu};
+use crate::input::{Bindings, InputAction};
+use crate::scenes::menu::MenuScene;
+use crate::scenes::stack::{Scene, SceneError, SceneResult, Transition};
+
+/// Shown over the stack when a scene fails.
+pub struct ErrorScene<'a, 'b> {
+    dispatcher: Dispatcher<'a, 'b>,
+    menu: Option<Menu>,
+}
+
+impl<'a, 'b> ErrorScene<'a, 'b> {
+    pub fn new(_ctx: &mut Context, world: &mut World, error: &SceneError) -> Self {
+        let mut menu = Menu::new("error".to_string());
+        menu.description = error.to_string();
+        menu.add_item(Action::Back, "main menu".to_string(), 10.0, true);
+        menu.add_item(Action::Quit, "quit".to_string(), 60.0, true);
+
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(UpdateGlobalState, "global_system", &[])
+            .with(UpdateMenu, "menu_system", &[])
+            .build();
+        dispatcher.setup(world);
+        Self {
+            dispatcher,
+            menu: Some(menu),
+        }
+    }
+}
+
+impl<'a, 'b> Scene for ErrorScene<'a, 'b> {
+    fn update(&mut self, _ctx: &mut Context, world: &mut World) -> SceneResult {
+        self.dispatcher.dispatch(world);
+
+        Ok(Transition::None)
+    }
+
+    fn draw(&mut self, _ctx: &mut Context, world: &mut World, canvas: &mut Canvas) -> GameResult {
+        let mut render = MenuRender::new(canvas);
+        render.run_now(world);
+        Ok(())
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        _input: KeyInput,
+        _repeat: bool,
+        world: &mut World,
+    ) -> SceneResult {
+        let pressed = world
+            .fetch::<Bindings>()
+            .find(&world.fetch::<KeyState>(), &[InputAction::Confirm]);
+        match pressed {
+            Some(InputAction::Confirm) => {
+                let action = world.fetch::<Menu>().get_currect_action();
+                match action {
+                    Action::Back => Ok(Transition::Reset(Box::new(MenuScene::new(ctx, world)))),
+                    Action::Quit => {
+                        ctx.request_quit();
+                        Ok(Transition::None)
+                    }
+                    _ => Ok(Transition::None),
+                }
+            }
+            _ => Ok(Transition::None),
+        }
+    }
+
+    fn menu(&mut self) -> Option<&mut Option<Menu>> {
+        Some(&mut self.menu)
+    }
+
+    fn name(&self) -> &str {
+        "Error"
+    }
+}
+
+impl<'a, 'b> fmt::Debug for ErrorScene<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
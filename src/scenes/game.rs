@@ -1,113 +1,113 @@
 use std::fmt;
 
+use ggez::event::MouseButton;
 use ggez::graphics::Canvas;
-use ggez::input::keyboard::KeyCode;
 use ggez::input::keyboard::KeyInput;
 use ggez::{Context, GameResult};
-use rand::Rng;
+use log::warn;
 use specs::prelude::*;
 
-use crate::consts::{HORIZONTAL_SPEED_MIN, PLAYER_LIFE, PLAYER_START_ANGLE, PLAYER_START_LEVEL};
-use crate::ecs::components::{ConstantMovement, Enemy, Form, Player, Position, View};
-use crate::ecs::resources::{GameState, GameTime};
-use crate::ecs::systems::{
-    Collision, GameRender, Music, UpdateGameState, UpdatePosition, UpdateTimer,
-};
-
+use crate::consts::AIM_PRECISION;
+use crate::ecs::resources::{GameState, HighScores, KeyState, Progress, Recorder, Screen, Story};
+use crate::ecs::systems::{GameRender, Music};
+use crate::input::{Bindings, InputAction};
+use crate::replay::{LAST_PATH, REPLAYS_DIR};
 use crate::scenes::curtain::CurtainScene;
 use crate::scenes::gameover::GameOverScene;
+use crate::scenes::intro::IntroScene;
+use crate::scenes::nameentry::NameEntryScene;
 use crate::scenes::pause::PauseScene;
-use crate::scenes::stack::{Scene, Transition};
-use crate::utils::{self, Direction, GameStatus};
+use crate::scenes::stack::{Scene, SceneResult, Transition};
+use crate::sim;
+use crate::soundtrack::Track;
+use crate::storage;
+use crate::utils::{self, Control, GameStatus, Mode};
 
 pub struct GameScene<'a, 'b> {
     dispatcher: Dispatcher<'a, 'b>,
+    music: Music,
 }
 
 impl<'a, 'b> GameScene<'a, 'b> {
     pub fn new(_ctx: &mut Context, world: &mut World) -> Self {
-        world.insert(GameTime::default());
+        sim::start_level(world);
 
-        world
-            .create_entity()
-            .with(Position::new(PLAYER_START_LEVEL, PLAYER_START_ANGLE))
-            .with(View {
-                form: Form::Triangle,
-                size: 8.0,
-            })
-            .with(Player {
-                life: PLAYER_LIFE,
-                speed: HORIZONTAL_SPEED_MIN,
-                speed_press_ms: 0.0,
-                start_angle_repeat: PLAYER_START_ANGLE,
-                collision: None,
-            })
-            .build();
-
-        let mut dir = Direction::Left(0.01);
-        for level in 1..=7 {
-            let element_map = utils::create_map_of_element(level);
-            for (angle, size) in element_map {
-                let color = rand::thread_rng().gen_range(0..2);
-                let mut builder = world
-                    .create_entity()
-                    .with(Position::new(level as i32, angle))
-                    .with(View {
-                        form: Form::Circle,
-                        size,
-                    });
-
-                if level % 2 != 0 {
-                    builder = builder.with(Enemy { color });
-                    builder = builder.with(ConstantMovement { direction: dir });
-                }
-                builder.build();
-            }
-            if level % 2 != 0 {
-                dir = Direction::inverse(dir);
+        let mut dispatcher = sim::dispatcher();
+        dispatcher.setup(world);
+        let mut music = Music::default();
+        System::setup(&mut music, world);
+        Self { dispatcher, music }
+    }
+
+    fn save_replay(ctx: &mut Context, world: &mut World) {
+        let score = world.fetch::<GameState>().score;
+        if let Some(replay) = world.fetch_mut::<Recorder>().finish(score) {
+            if let Err(e) = ctx.fs.create_dir(REPLAYS_DIR) {
+                warn!("Can't create {}: {}", REPLAYS_DIR, e);
             }
+            storage::save(ctx, LAST_PATH, &replay);
         }
+    }
 
-        let mut dispatcher = DispatcherBuilder::new()
-            .with(UpdateTimer, "time_system", &[])
-            .with(UpdatePosition, "pos_system", &["time_system"])
-            .with(Collision, "collision_system", &["pos_system"])
-            .with(UpdateGameState, "game_system", &["time_system"])
-            .build();
-        dispatcher.setup(world);
-        Self { dispatcher }
+    /// Name entry when the run made the high scores, then the end screen.
+    fn run_finished(ctx: &mut Context, world: &mut World, completed: bool) -> Transition {
+        let (mode, score) = {
+            let gs = world.fetch::<GameState>();
+            (gs.mode, gs.score)
+        };
+        if world.fetch::<HighScores>().qualifies(mode, score) {
+            return Transition::Replace(Box::new(NameEntryScene::new(ctx, world, completed)));
+        }
+        Transition::Replace(Box::new(GameOverScene::new(ctx, world, completed)))
+    }
+
+    fn chapter_completed(ctx: &mut Context, world: &mut World, chapter: usize) -> Transition {
+        GameScene::save_replay(ctx, world);
+
+        let next = world.fetch::<Story>().next_after(chapter);
+        world
+            .fetch_mut::<Progress>()
+            .unlock(next.unwrap_or(chapter + 1));
+        storage::save(ctx, Progress::PATH, &*world.fetch::<Progress>());
+
+        let next = match next {
+            Some(next) => next,
+            None => return GameScene::run_finished(ctx, world, true),
+        };
+        world.fetch_mut::<GameState>().mode = Mode::Story(next);
+        Transition::Replace(Box::new(IntroScene::new(ctx, world, next, false)))
     }
 }
 
 impl<'a, 'b> Scene for GameScene<'a, 'b> {
-    fn update(&mut self, ctx: &mut Context, world: &mut World) -> Result<Transition, String> {
+    fn update(&mut self, ctx: &mut Context, world: &mut World) -> SceneResult {
+        {
+            let control = world.fetch::<GameState>().control.clone();
+            world.fetch_mut::<Recorder>().record(
+                &control,
+                &world.fetch::<KeyState>(),
+                &world.fetch::<Bindings>(),
+            );
+        }
         self.dispatcher.dispatch(world);
-        // context :(
-        let mut mrs = Music::new(ctx);
-        mrs.run_now(world);
+        self.music.run_now(world);
 
-        let status = world.fetch::<GameState>().status.clone();
-        let score = world.fetch::<GameTime>().timer.as_secs();
+        let mode = world.fetch::<GameState>().mode;
 
-        return match status {
+        match sim::finish_level(world) {
+            Some(_) if mode == Mode::Test => Ok(Transition::Pop),
             Some(GameStatus::GameOver) => {
-                world.delete_all();
-                world.maintain();
-                world.fetch_mut::<GameState>().status = None;
-                Ok(Transition::Replace(Box::new(GameOverScene::new(
-                    ctx, world,
-                ))))
+                GameScene::save_replay(ctx, world);
+                Ok(GameScene::run_finished(ctx, world, false))
             }
             Some(GameStatus::LevelCompleted) => {
-                world.delete_all();
-                world.maintain();
-                world.fetch_mut::<GameState>().status = None;
-                world.fetch_mut::<GameState>().game_level += 1;
-                world.fetch_mut::<GameState>().score += score;
+                if let Mode::Story(chapter) = mode {
+                    return Ok(GameScene::chapter_completed(ctx, world, chapter));
+                }
                 Ok(Transition::Push(Box::new(CurtainScene::new(world, false))))
             }
             None => Ok(Transition::None),
-        };
+        }
     }
 
     fn draw(&mut self, ctx: &mut Context, world: &mut World, canvas: &mut Canvas) -> GameResult {
@@ -119,14 +119,61 @@ impl<'a, 'b> Scene for GameScene<'a, 'b> {
     fn key_down_event(
         &mut self,
         ctx: &mut Context,
-        input: KeyInput,
+        _input: KeyInput,
         _repeat: bool,
         world: &mut World,
-    ) -> Result<Transition, String> {
-        match input.keycode {
-            Some(KeyCode::Return) => Ok(Transition::Push(Box::new(PauseScene::new(ctx, world)))),
-            _ => Ok(Transition::None),
+    ) -> SceneResult {
+        if world
+            .fetch::<Bindings>()
+            .is(&world.fetch::<KeyState>(), InputAction::Pause)
+        {
+            return Ok(Transition::Push(Box::new(PauseScene::new(ctx, world))));
+        }
+        Ok(Transition::None)
+    }
+
+    fn focus_event(&mut self, ctx: &mut Context, gained: bool, world: &mut World) -> SceneResult {
+        if !gained {
+            return Ok(Transition::Push(Box::new(PauseScene::new(ctx, world))));
+        }
+        Ok(Transition::None)
+    }
+
+    fn mouse_motion_event(
+        &mut self,
+        _ctx: &mut Context,
+        x: f32,
+        y: f32,
+        world: &mut World,
+    ) -> SceneResult {
+        if world.fetch::<GameState>().control == Control::Mouse {
+            let [cx, cy] = world.fetch::<Screen>().center();
+            let angle = utils::normalize_angle((y - cy).atan2(x - cx));
+            // Rounded so replays reproduce the same turn
+            world.fetch_mut::<KeyState>().aim =
+                Some((angle * AIM_PRECISION).round() / AIM_PRECISION);
         }
+        Ok(Transition::None)
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut Context,
+        button: MouseButton,
+        _x: f32,
+        _y: f32,
+        world: &mut World,
+    ) -> SceneResult {
+        if button == MouseButton::Left && world.fetch::<GameState>().control == Control::Mouse {
+            world
+                .fetch_mut::<KeyState>()
+                .press(vec![InputAction::MoveInward]);
+        }
+        Ok(Transition::None)
+    }
+
+    fn track(&self) -> Option<Track> {
+        Some(Track::Game)
     }
 
     fn name(&self) -> &str {
@@ -1,50 +1,68 @@
 use std::fmt;
 
 use ggez::graphics::Canvas;
-use ggez::input::keyboard::KeyCode;
 use ggez::input::keyboard::KeyInput;
 use ggez::{Context, GameResult};
 use specs::prelude::*;
 
-use crate::ecs::resources::{Action, GameState, Menu};
+use crate::ecs::resources::{Action, GameState, KeyState, Menu, Seed};
 use crate::ecs::systems::{MenuRender, UpdateGlobalState, UpdateMenu};
+use crate::input::{Bindings, InputAction};
 use crate::scenes::curtain::CurtainScene;
 use crate::scenes::game::GameScene;
-use crate::scenes::stack::{Scene, Transition};
+use crate::scenes::menu::MenuScene;
+use crate::scenes::replay::ReplayScene;
+use crate::scenes::stack::{Scene, SceneResult, Transition};
+use crate::sim;
+use crate::soundtrack::Track;
 
 pub struct GameOverScene<'a, 'b> {
     dispatcher: Dispatcher<'a, 'b>,
+    menu: Option<Menu>,
 }
 
 impl<'a, 'b> GameOverScene<'a, 'b> {
-    pub fn new(_ctx: &mut Context, world: &mut World) -> Self {
-        let mut menu = Menu::new("game over".to_string());
-        menu.add_item(Action::Continue, "continue".to_string(), 10.0, true);
+    /// `completed` is true when the last story chapter was finished.
+    pub fn new(_ctx: &mut Context, world: &mut World, completed: bool) -> Self {
+        let title = if completed {
+            "story completed"
+        } else {
+            "game over"
+        };
+        let mut menu = Menu::new(title.to_string());
+        if completed {
+            menu.add_item(Action::Back, "main menu".to_string(), 10.0, true);
+        } else {
+            menu.add_item(Action::Continue, "continue".to_string(), 10.0, true);
+        }
+        menu.add_item(Action::Replay, "watch replay".to_string(), 10.0, true);
         menu.add_item(Action::Quit, "quit".to_string(), 60.0, true);
 
         let score = world.fetch::<GameState>().score;
         menu.subtitle = format!("score{:.>9}", score);
-
-        world.insert(menu);
+        menu.description = format!("seed {}", world.fetch::<Seed>().value);
 
         let mut dispatcher = DispatcherBuilder::new()
             .with(UpdateGlobalState, "global_system", &[])
             .with(UpdateMenu, "menu_system", &[])
             .build();
         dispatcher.setup(world);
-        Self { dispatcher }
+        Self {
+            dispatcher,
+            menu: Some(menu),
+        }
     }
 }
 
 impl<'a, 'b> Scene for GameOverScene<'a, 'b> {
-    fn update(&mut self, _ctx: &mut Context, world: &mut World) -> Result<Transition, String> {
+    fn update(&mut self, _ctx: &mut Context, world: &mut World) -> SceneResult {
         self.dispatcher.dispatch(world);
 
         Ok(Transition::None)
     }
 
-    fn draw(&mut self, ctx: &mut Context, world: &mut World, canvas: &mut Canvas) -> GameResult {
-        let mut render = MenuRender::new(ctx, canvas);
+    fn draw(&mut self, _ctx: &mut Context, world: &mut World, canvas: &mut Canvas) -> GameResult {
+        let mut render = MenuRender::new(canvas);
         render.run_now(world);
         Ok(())
     }
@@ -52,16 +70,21 @@ impl<'a, 'b> Scene for GameOverScene<'a, 'b> {
     fn key_down_event(
         &mut self,
         ctx: &mut Context,
-        input: KeyInput,
+        _input: KeyInput,
         _repeat: bool,
         world: &mut World,
-    ) -> Result<Transition, String> {
-        match input.keycode {
-            Some(KeyCode::Return) => {
+    ) -> SceneResult {
+        let pressed = world.fetch::<Bindings>().find(
+            &world.fetch::<KeyState>(),
+            &[InputAction::Confirm, InputAction::Back],
+        );
+        match pressed {
+            Some(InputAction::Confirm) => {
                 let action = world.fetch::<Menu>().get_currect_action();
                 match action {
                     Action::Continue => {
-                        world.fetch_mut::<GameState>().reset_result();
+                        let mode = world.fetch::<GameState>().mode;
+                        sim::start_run(world, mode);
                         Ok(Transition::MultiReplace(
                             vec![
                                 Box::new(GameScene::new(ctx, world)),
@@ -70,6 +93,8 @@ impl<'a, 'b> Scene for GameOverScene<'a, 'b> {
                             1,
                         ))
                     }
+                    Action::Back => Ok(Transition::Replace(Box::new(MenuScene::new(ctx, world)))),
+                    Action::Replay => Ok(ReplayScene::open_last(ctx, world)),
                     Action::Quit => {
                         ctx.request_quit();
                         Ok(Transition::None)
@@ -77,11 +102,21 @@ impl<'a, 'b> Scene for GameOverScene<'a, 'b> {
                     _ => Ok(Transition::None),
                 }
             }
-            Some(KeyCode::Escape) => Ok(Transition::Pop),
+            Some(InputAction::Back) => {
+                Ok(Transition::Replace(Box::new(MenuScene::new(ctx, world))))
+            }
             _ => Ok(Transition::None),
         }
     }
 
+    fn menu(&mut self) -> Option<&mut Option<Menu>> {
+        Some(&mut self.menu)
+    }
+
+    fn track(&self) -> Option<Track> {
+        Some(Track::GameOver)
+    }
+
     fn name(&self) -> &str {
         "GameOver"
     }
@@ -0,0 +1,139 @@
+use std::fmt;
+
+use ggez::graphics::Canvas;
+use ggez::input::keyboard::KeyInput;
+use ggez::{Context, GameResult};
+use specs::prelude::*;
+
+use crate::consts::{
+    MENU_CHAR_WIDTH, MENU_DESCRIPTION_SIZE, MENU_FOOTER, MENU_LEFT, MENU_LINE_SPACING,
+};
+use crate::ecs::resources::{Action, HighScores, KeyState, Menu, Screen};
+use crate::ecs::systems::{MenuRender, UpdateGlobalState};
+use crate::input::{Bindings, InputAction};
+use crate::scenes::stack::{Scene, SceneResult, Transition};
+use crate::utils::Mode;
+
+const MODES: [Mode; 2] = [Mode::Endless, Mode::Story(0)];
+
+pub struct HighScoreScene<'a, 'b> {
+    dispatcher: Dispatcher<'a, 'b>,
+    menu: Option<Menu>,
+    mode: usize,
+}
+
+impl<'a, 'b> HighScoreScene<'a, 'b> {
+    pub fn new(_ctx: &mut Context, world: &mut World) -> Self {
+        let mut menu = Menu::new("high scores".to_string());
+        menu.add_item(Action::Back, "back".to_string(), 60.0, true);
+
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(UpdateGlobalState, "global_system", &[])
+            .build();
+        dispatcher.setup(world);
+
+        HighScoreScene::show_table(world, &mut menu, MODES[0]);
+
+        Self {
+            dispatcher,
+            menu: Some(menu),
+            mode: 0,
+        }
+    }
+
+    fn show_table(world: &World, menu: &mut Menu, mode: Mode) {
+        let scores = world.fetch::<HighScores>();
+
+        let mut description = String::new();
+        for (i, s) in scores.table(mode).iter().enumerate() {
+            description.push_str(&format!(
+                "{:>2}. {:<10} {:>6} lvl {:>2}  {}  seed {}\n",
+                i + 1,
+                s.name,
+                s.score,
+                s.level,
+                s.date,
+                s.seed
+            ));
+        }
+        if description.is_empty() {
+            description.push_str("no runs yet\n");
+        }
+        description.push_str("\n[left/right] switch mode");
+
+        menu.subtitle = match mode {
+            Mode::Story(_) => "story".to_string(),
+            _ => "endless".to_string(),
+        };
+        menu.description = description;
+
+        // Shrink the table until it fits right of the menu and above the footer
+        let rect = world.fetch::<Screen>().rect();
+        let longest = menu.description.lines().map(|l| l.chars().count()).max();
+        let width = (rect.right() - MENU_LEFT) / (longest.unwrap_or(1) as f32 * MENU_CHAR_WIDTH);
+        menu.description_size = 0.0;
+        let rest = menu
+            .item_positions()
+            .last()
+            .map_or(0.0, |rect| rect.bottom());
+        let lines = (menu.description.lines().count() + 1) as f32;
+        let height = (rect.bottom() - MENU_FOOTER - rest) / (lines * MENU_LINE_SPACING);
+        menu.description_size = width.min(height).min(MENU_DESCRIPTION_SIZE);
+    }
+}
+
+impl<'a, 'b> Scene for HighScoreScene<'a, 'b> {
+    fn update(&mut self, _ctx: &mut Context, world: &mut World) -> SceneResult {
+        self.dispatcher.dispatch(world);
+        // Laid out again every frame, so it follows the window size
+        HighScoreScene::show_table(world, &mut world.fetch_mut::<Menu>(), MODES[self.mode]);
+
+        Ok(Transition::None)
+    }
+
+    fn draw(&mut self, _ctx: &mut Context, world: &mut World, canvas: &mut Canvas) -> GameResult {
+        let mut render = MenuRender::new(canvas);
+        render.run_now(world);
+        Ok(())
+    }
+
+    fn key_down_event(
+        &mut self,
+        _ctx: &mut Context,
+        _input: KeyInput,
+        _repeat: bool,
+        world: &mut World,
+    ) -> SceneResult {
+        let pressed = world.fetch::<Bindings>().find(
+            &world.fetch::<KeyState>(),
+            &[
+                InputAction::MoveClockwise,
+                InputAction::MoveCounterClockwise,
+                InputAction::Confirm,
+                InputAction::Back,
+            ],
+        );
+        match pressed {
+            Some(InputAction::MoveClockwise) | Some(InputAction::MoveCounterClockwise) => {
+                self.mode = (self.mode + 1) % MODES.len();
+                Ok(Transition::None)
+            }
+            Some(InputAction::Confirm) | Some(InputAction::Back) => Ok(Transition::Pop),
+            _ => Ok(Transition::None),
+        }
+    }
+
+    fn menu(&mut self) -> Option<&mut Option<Menu>> {
+        Some(&mut self.menu)
+    }
+
+    fn name(&self) -> &str {
+        "HighScores"
+    }
+}
+
+impl<'a, 'b> fmt::Debug for HighScoreScene<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+
+use ggez::graphics::Canvas;
+use ggez::input::keyboard::KeyInput;
+use ggez::{Context, GameResult};
+use specs::prelude::*;
+
+use crate::ecs::resources::{Action, KeyState, Menu, Story};
+use crate::ecs::systems::{MenuRender, UpdateGlobalState, UpdateMenu};
+use crate::input::{Bindings, InputAction};
+use crate::scenes::chapters::ChapterScene;
+use crate::scenes::curtain::CurtainScene;
+use crate::scenes::game::GameScene;
+use crate::scenes::menu::MenuScene;
+use crate::scenes::stack::{Scene, SceneResult, Transition};
+use crate::sim;
+use crate::soundtrack::Track;
+use crate::utils::Mode;
+
+pub struct IntroScene<'a, 'b> {
+    dispatcher: Dispatcher<'a, 'b>,
+    menu: Option<Menu>,
+    chapter: usize,
+    from_menu: bool,
+}
+
+impl<'a, 'b> IntroScene<'a, 'b> {
+    /// `from_menu` is true when the intro replaces the chapter select
+    /// that was pushed over the main menu.
+    pub fn new(_ctx: &mut Context, world: &mut World, chapter: usize, from_menu: bool) -> Self {
+        let mut menu = Menu::new(format!("chapter {}", chapter + 1));
+        {
+            let story = world.fetch::<Story>();
+            if let Some(level) = story.chapter(chapter) {
+                menu.subtitle = level.name.clone();
+                menu.description = level.intro.clone();
+            }
+        }
+        menu.add_item(Action::Start, "start".to_string(), 10.0, true);
+        menu.add_item(Action::Back, "back".to_string(), 60.0, true);
+
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(UpdateGlobalState, "global_system", &[])
+            .with(UpdateMenu, "menu_system", &[])
+            .build();
+        dispatcher.setup(world);
+        Self {
+            dispatcher,
+            menu: Some(menu),
+            chapter,
+            from_menu,
+        }
+    }
+
+    fn back(&self, ctx: &mut Context, world: &mut World) -> Transition {
+        if self.from_menu {
+            Transition::Replace(Box::new(ChapterScene::new(ctx, world)))
+        } else {
+            Transition::Replace(Box::new(MenuScene::new(ctx, world)))
+        }
+    }
+}
+
+impl<'a, 'b> Scene for IntroScene<'a, 'b> {
+    fn update(&mut self, _ctx: &mut Context, world: &mut World) -> SceneResult {
+        self.dispatcher.dispatch(world);
+
+        Ok(Transition::None)
+    }
+
+    fn draw(&mut self, _ctx: &mut Context, world: &mut World, canvas: &mut Canvas) -> GameResult {
+        let mut render = MenuRender::new(canvas);
+        render.run_now(world);
+        Ok(())
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        _input: KeyInput,
+        _repeat: bool,
+        world: &mut World,
+    ) -> SceneResult {
+        let pressed = world.fetch::<Bindings>().find(
+            &world.fetch::<KeyState>(),
+            &[InputAction::Confirm, InputAction::Back],
+        );
+        match pressed {
+            Some(InputAction::Confirm) => {
+                let action = world.fetch::<Menu>().get_currect_action();
+                match action {
+                    Action::Start => {
+                        // Chapters played one after another add up to one run
+                        if self.from_menu {
+                            sim::start_run(world, Mode::Story(self.chapter));
+                        } else {
+                            sim::continue_run(world, Mode::Story(self.chapter));
+                        }
+                        Ok(Transition::MultiReplace(
+                            vec![
+                                Box::new(GameScene::new(ctx, world)),
+                                Box::new(CurtainScene::new(world, true)),
+                            ],
+                            if self.from_menu { 2 } else { 1 },
+                        ))
+                    }
+                    Action::Back => Ok(self.back(ctx, world)),
+                    _ => Ok(Transition::None),
+                }
+            }
+            Some(InputAction::Back) => Ok(self.back(ctx, world)),
+            _ => Ok(Transition::None),
+        }
+    }
+
+    fn menu(&mut self) -> Option<&mut Option<Menu>> {
+        Some(&mut self.menu)
+    }
+
+    fn track(&self) -> Option<Track> {
+        Some(Track::Menu)
+    }
+
+    fn name(&self) -> &str {
+        "Intro"
+    }
+}
+
+impl<'a, 'b> fmt::Debug for IntroScene<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
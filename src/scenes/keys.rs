@@ -0,0 +1,175 @@
+use std::fmt;
+
+use ggez::graphics::Canvas;
+use ggez::input::keyboard::KeyInput;
+use ggez::{Context, GameResult};
+use specs::prelude::*;
+
+use crate::ecs::resources::{Action, KeyState, Menu};
+use crate::ecs::systems::{MenuRender, UpdateMenu};
+use crate::input::{Bindings, InputAction};
+use crate::scenes::stack::{Scene, SceneResult, Transition};
+use crate::storage;
+
+const HINT: &str = "[confirm] rebind";
+
+/// Rebinds the keyboard keys of every action and saves them to the
+/// bindings file.
+pub struct KeysScene<'a, 'b> {
+    dispatcher: Dispatcher<'a, 'b>,
+    menu: Option<Menu>,
+    /// Action waiting for its new key.
+    capture: Option<InputAction>,
+}
+
+impl<'a, 'b> KeysScene<'a, 'b> {
+    pub fn new(_ctx: &mut Context, world: &mut World) -> Self {
+        let mut menu = Menu::new("keys".to_string());
+        for action in InputAction::ALL {
+            menu.add_item(Action::Rebind(action), String::new(), 10.0, true);
+        }
+        menu.add_item(
+            Action::ResetKeys,
+            "reset to defaults".to_string(),
+            10.0,
+            true,
+        );
+        menu.add_item(Action::Back, "back".to_string(), 60.0, true);
+        menu.description = HINT.to_string();
+        KeysScene::show_keys(&mut menu, &world.fetch::<Bindings>());
+
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(UpdateMenu, "menu_system", &[])
+            .build();
+        dispatcher.setup(world);
+        Self {
+            dispatcher,
+            menu: Some(menu),
+            capture: None,
+        }
+    }
+
+    fn show_keys(menu: &mut Menu, bindings: &Bindings) {
+        for item in &mut menu.items {
+            if let Action::Rebind(action) = item.action {
+                let keys: Vec<String> = bindings
+                    .keys(action)
+                    .iter()
+                    .map(|key| format!("{:?}", key))
+                    .collect();
+                item.text = format!("{}: {}", action, keys.join(", "));
+            }
+        }
+    }
+
+    fn save(ctx: &mut Context, world: &mut World) {
+        let bindings = world.fetch::<Bindings>();
+        storage::save(ctx, Bindings::PATH, &*bindings);
+        KeysScene::show_keys(&mut world.fetch_mut::<Menu>(), &bindings);
+    }
+}
+
+impl<'a, 'b> Scene for KeysScene<'a, 'b> {
+    fn update(&mut self, _ctx: &mut Context, world: &mut World) -> SceneResult {
+        if self.capture.is_none() {
+            self.dispatcher.dispatch(world);
+        }
+
+        Ok(Transition::None)
+    }
+
+    fn draw(&mut self, _ctx: &mut Context, world: &mut World, canvas: &mut Canvas) -> GameResult {
+        let mut render = MenuRender::new(canvas);
+        render.run_now(world);
+        Ok(())
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        input: KeyInput,
+        repeat: bool,
+        world: &mut World,
+    ) -> SceneResult {
+        // Still the key that started the capture
+        if repeat && self.capture.is_some() {
+            return Ok(Transition::None);
+        }
+        if let Some(action) = self.capture.take() {
+            // Any key is taken, Escape too; a gamepad or mouse press cancels
+            let mut description = HINT.to_string();
+            if let Some(key) = input.keycode {
+                let taken = world.fetch_mut::<Bindings>().rebind(action, key);
+                if let Some(other) = taken {
+                    description = format!("{:?} was moved from {}", key, other);
+                }
+                KeysScene::save(ctx, world);
+            }
+            world.fetch_mut::<KeyState>().key = None;
+            world.fetch_mut::<Menu>().description = description;
+            return Ok(Transition::None);
+        }
+
+        let pressed = world.fetch::<Bindings>().find(
+            &world.fetch::<KeyState>(),
+            &[InputAction::Confirm, InputAction::Back],
+        );
+        match pressed {
+            Some(InputAction::Confirm) => {
+                let action = world.fetch::<Menu>().get_currect_action();
+                match action {
+                    Action::Rebind(action) => {
+                        self.capture = Some(action);
+                        world.fetch_mut::<Menu>().description =
+                            format!("press a key for {}", action);
+                        Ok(Transition::None)
+                    }
+                    Action::ResetKeys => {
+                        let pad = world.fetch::<Bindings>().pad.clone();
+                        *world.fetch_mut::<Bindings>() = Bindings {
+                            pad,
+                            ..Default::default()
+                        };
+                        KeysScene::save(ctx, world);
+                        Ok(Transition::None)
+                    }
+                    Action::Back => Ok(Transition::Pop),
+                    _ => Ok(Transition::None),
+                }
+            }
+            Some(InputAction::Back) => Ok(Transition::Pop),
+            _ => Ok(Transition::None),
+        }
+    }
+
+    fn mouse_motion_event(
+        &mut self,
+        _ctx: &mut Context,
+        x: f32,
+        y: f32,
+        world: &mut World,
+    ) -> SceneResult {
+        if self.capture.is_none() {
+            world.fetch_mut::<Menu>().hover(x, y);
+        }
+        Ok(Transition::None)
+    }
+
+    fn menu(&mut self) -> Option<&mut Option<Menu>> {
+        Some(&mut self.menu)
+    }
+
+    fn captures_keys(&self) -> bool {
+        self.capture.is_some()
+    }
+
+    fn name(&self) -> &str {
+        "Keys"
+    }
+}
+
+impl<'a, 'b> fmt::Debug for KeysScene<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -1,48 +1,64 @@
 use std::fmt;
 
 use ggez::graphics::Canvas;
-use ggez::input::keyboard::KeyCode;
 use ggez::input::keyboard::KeyInput;
 use ggez::{Context, GameResult};
 use specs::prelude::*;
 
-use crate::ecs::resources::{Action, Menu};
+use crate::ecs::resources::{Action, KeyState, Menu};
 use crate::ecs::systems::{MenuRender, UpdateGlobalState, UpdateMenu};
+use crate::input::{Bindings, InputAction};
+use crate::replay::LAST_PATH;
+use crate::scenes::chapters::ChapterScene;
 use crate::scenes::curtain::CurtainScene;
+use crate::scenes::editor::EditorScene;
 use crate::scenes::game::GameScene;
-use crate::scenes::stack::{Scene, Transition};
+use crate::scenes::highscores::HighScoreScene;
+use crate::scenes::options::OptionsScene;
+use crate::scenes::replay::ReplayScene;
+use crate::scenes::stack::{Scene, SceneResult, Transition};
+use crate::sim;
+use crate::soundtrack::Track;
+use crate::utils::Mode;
 
 pub struct MenuScene<'a, 'b> {
     dispatcher: Dispatcher<'a, 'b>,
+    menu: Option<Menu>,
 }
 
 impl<'a, 'b> MenuScene<'a, 'b> {
-    pub fn new(_ctx: &mut Context, world: &mut World) -> Self {
+    pub fn new(ctx: &mut Context, world: &mut World) -> Self {
         let mut menu = Menu::new("To_Center".to_string());
-        menu.add_item(Action::StoryMode, "story mode".to_string(), 10.0, false);
+        menu.add_item(Action::StoryMode, "story mode".to_string(), 10.0, true);
         menu.add_item(Action::EndlessMode, "endless mode".to_string(), 10.0, true);
+        menu.add_item(Action::Editor, "level editor".to_string(), 10.0, true);
+        let has_replay = ctx.fs.exists(LAST_PATH);
+        menu.add_item(Action::Replay, "watch replay".to_string(), 10.0, has_replay);
+        menu.add_item(Action::HighScores, "high scores".to_string(), 10.0, true);
+        menu.add_item(Action::Options, "options".to_string(), 10.0, true);
         menu.add_item(Action::Quit, "quit".to_string(), 60.0, true);
 
-        world.insert(menu);
-
         let mut dispatcher = DispatcherBuilder::new()
             .with(UpdateGlobalState, "global_system", &[])
             .with(UpdateMenu, "menu_system", &[])
             .build();
         dispatcher.setup(world);
-        Self { dispatcher }
+        Self {
+            dispatcher,
+            menu: Some(menu),
+        }
     }
 }
 
 impl<'a, 'b> Scene for MenuScene<'a, 'b> {
-    fn update(&mut self, _ctx: &mut Context, world: &mut World) -> Result<Transition, String> {
+    fn update(&mut self, _ctx: &mut Context, world: &mut World) -> SceneResult {
         self.dispatcher.dispatch(world);
 
         Ok(Transition::None)
     }
 
-    fn draw(&mut self, ctx: &mut Context, world: &mut World, canvas: &mut Canvas) -> GameResult {
-        let mut render = MenuRender::new(ctx, canvas);
+    fn draw(&mut self, _ctx: &mut Context, world: &mut World, canvas: &mut Canvas) -> GameResult {
+        let mut render = MenuRender::new(canvas);
         render.run_now(world);
         Ok(())
     }
@@ -50,21 +66,38 @@ impl<'a, 'b> Scene for MenuScene<'a, 'b> {
     fn key_down_event(
         &mut self,
         ctx: &mut Context,
-        input: KeyInput,
+        _input: KeyInput,
         _repeat: bool,
         world: &mut World,
-    ) -> Result<Transition, String> {
-        match input.keycode {
-            Some(KeyCode::Return) => {
+    ) -> SceneResult {
+        let pressed = world
+            .fetch::<Bindings>()
+            .find(&world.fetch::<KeyState>(), &[InputAction::Confirm]);
+        match pressed {
+            Some(InputAction::Confirm) => {
                 let action = world.fetch::<Menu>().get_currect_action();
                 match action {
-                    Action::EndlessMode => Ok(Transition::MultiReplace(
-                        vec![
-                            Box::new(GameScene::new(ctx, world)),
-                            Box::new(CurtainScene::new(world, true)),
-                        ],
-                        1,
-                    )),
+                    Action::StoryMode => {
+                        Ok(Transition::Push(Box::new(ChapterScene::new(ctx, world))))
+                    }
+                    Action::EndlessMode => {
+                        sim::start_run(world, Mode::Endless);
+                        Ok(Transition::MultiReplace(
+                            vec![
+                                Box::new(GameScene::new(ctx, world)),
+                                Box::new(CurtainScene::new(world, true)),
+                            ],
+                            1,
+                        ))
+                    }
+                    Action::Editor => Ok(Transition::Push(Box::new(EditorScene::new(ctx, world)))),
+                    Action::Replay => Ok(ReplayScene::open_last(ctx, world)),
+                    Action::HighScores => {
+                        Ok(Transition::Push(Box::new(HighScoreScene::new(ctx, world))))
+                    }
+                    Action::Options => {
+                        Ok(Transition::Push(Box::new(OptionsScene::new(ctx, world))))
+                    }
                     Action::Quit => {
                         ctx.request_quit();
                         Ok(Transition::None)
@@ -76,6 +109,14 @@ impl<'a, 'b> Scene for MenuScene<'a, 'b> {
         }
     }
 
+    fn menu(&mut self) -> Option<&mut Option<Menu>> {
+        Some(&mut self.menu)
+    }
+
+    fn track(&self) -> Option<Track> {
+        Some(Track::Menu)
+    }
+
     fn name(&self) -> &str {
         "Menu"
     }
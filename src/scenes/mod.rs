@@ -1,6 +1,15 @@
+pub mod chapters;
 pub mod curtain;
+pub mod editor;
+pub mod error;
 pub mod game;
 pub mod gameover;
+pub mod highscores;
+pub mod intro;
+pub mod keys;
 pub mod menu;
+pub mod nameentry;
+pub mod options;
 pub mod pause;
+pub mod replay;
 pub mod stack;
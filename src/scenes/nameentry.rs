@@ -0,0 +1,151 @@
+use std::fmt;
+
+use ggez::graphics::Canvas;
+use ggez::input::keyboard::KeyCode;
+use ggez::input::keyboard::KeyInput;
+use ggez::{Context, GameResult};
+use specs::prelude::*;
+
+use crate::consts::NAME_LENGTH;
+use crate::ecs::resources::{Action, GameState, HighScores, KeyState, Menu, Score, Seed};
+use crate::ecs::systems::{MenuRender, UpdateGlobalState};
+use crate::input::{Bindings, InputAction};
+use crate::scenes::gameover::GameOverScene;
+use crate::scenes::stack::{Scene, SceneResult, Transition};
+use crate::soundtrack::Track;
+use crate::storage;
+use crate::utils;
+
+/// Asks for a name when a finished run makes it into the high scores.
+pub struct NameEntryScene<'a, 'b> {
+    dispatcher: Dispatcher<'a, 'b>,
+    menu: Option<Menu>,
+    name: String,
+    /// Passed on to the end screen.
+    completed: bool,
+}
+
+impl<'a, 'b> NameEntryScene<'a, 'b> {
+    pub fn new(_ctx: &mut Context, world: &mut World, completed: bool) -> Self {
+        let mut menu = Menu::new("new record".to_string());
+        menu.add_item(Action::Continue, "save".to_string(), 10.0, true);
+        menu.description = format!("score {}\ntype your name", world.fetch::<GameState>().score);
+
+        let name = world.fetch::<HighScores>().last_name.clone();
+        NameEntryScene::show_name(&mut menu, &name);
+
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(UpdateGlobalState, "global_system", &[])
+            .build();
+        dispatcher.setup(world);
+        Self {
+            dispatcher,
+            menu: Some(menu),
+            name,
+            completed,
+        }
+    }
+
+    fn show_name(menu: &mut Menu, name: &str) {
+        menu.subtitle = format!("{}_", name);
+    }
+
+    fn save(&self, ctx: &mut Context, world: &mut World) {
+        let (mode, entry) = {
+            let gs = world.fetch::<GameState>();
+            let entry = Score {
+                name: self.name.trim().to_string(),
+                score: gs.score,
+                level: gs.game_level,
+                seed: world.fetch::<Seed>().value,
+                date: utils::today(),
+            };
+            (gs.mode, entry)
+        };
+
+        let mut scores = world.fetch_mut::<HighScores>();
+        scores.insert(mode, entry);
+        storage::save_data(ctx, HighScores::PATH, &*scores);
+    }
+}
+
+impl<'a, 'b> Scene for NameEntryScene<'a, 'b> {
+    fn update(&mut self, _ctx: &mut Context, world: &mut World) -> SceneResult {
+        self.dispatcher.dispatch(world);
+
+        Ok(Transition::None)
+    }
+
+    fn draw(&mut self, _ctx: &mut Context, world: &mut World, canvas: &mut Canvas) -> GameResult {
+        let mut render = MenuRender::new(canvas);
+        render.run_now(world);
+        Ok(())
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        input: KeyInput,
+        _repeat: bool,
+        world: &mut World,
+    ) -> SceneResult {
+        if input.keycode == Some(KeyCode::Back) {
+            self.name.pop();
+            NameEntryScene::show_name(&mut world.fetch_mut::<Menu>(), &self.name);
+            return Ok(Transition::None);
+        }
+
+        let pressed = world.fetch::<Bindings>().find(
+            &world.fetch::<KeyState>(),
+            &[InputAction::Confirm, InputAction::Back],
+        );
+        match pressed {
+            Some(InputAction::Confirm) if !self.name.trim().is_empty() => {
+                self.save(ctx, world);
+                Ok(Transition::Replace(Box::new(GameOverScene::new(
+                    ctx,
+                    world,
+                    self.completed,
+                ))))
+            }
+            Some(InputAction::Back) => Ok(Transition::Replace(Box::new(GameOverScene::new(
+                ctx,
+                world,
+                self.completed,
+            )))),
+            _ => Ok(Transition::None),
+        }
+    }
+
+    fn text_input_event(
+        &mut self,
+        _ctx: &mut Context,
+        character: char,
+        world: &mut World,
+    ) -> SceneResult {
+        let allowed = character.is_alphanumeric() || " -_.".contains(character);
+        if allowed && self.name.chars().count() < NAME_LENGTH {
+            self.name.push(character);
+            NameEntryScene::show_name(&mut world.fetch_mut::<Menu>(), &self.name);
+        }
+        Ok(Transition::None)
+    }
+
+    fn menu(&mut self) -> Option<&mut Option<Menu>> {
+        Some(&mut self.menu)
+    }
+
+    fn track(&self) -> Option<Track> {
+        Some(Track::GameOver)
+    }
+
+    fn name(&self) -> &str {
+        "NameEntry"
+    }
+}
+
+impl<'a, 'b> fmt::Debug for NameEntryScene<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -0,0 +1,214 @@
+use std::fmt;
+
+use ggez::graphics::Canvas;
+use ggez::input::keyboard::KeyInput;
+use ggez::{Context, GameResult};
+use specs::prelude::*;
+
+use crate::consts::{GAME_SPEED_MAX, GAME_SPEED_MIN, GAME_SPEED_STEP, VOLUME_STEP};
+use crate::ecs::resources::{Action, GameState, ItemKind, KeyState, Menu, Settings};
+use crate::ecs::systems::{MenuRender, UpdateMenu};
+use crate::input::{Bindings, InputAction};
+use crate::scenes::keys::KeysScene;
+use crate::scenes::stack::{Scene, SceneResult, Transition};
+use crate::themes::Themes;
+use crate::utils::Control;
+
+const CONTROLS: [Control; 3] = [Control::Normal, Control::Advanced, Control::Mouse];
+
+pub struct OptionsScene<'a, 'b> {
+    dispatcher: Dispatcher<'a, 'b>,
+    menu: Option<Menu>,
+}
+
+impl<'a, 'b> OptionsScene<'a, 'b> {
+    pub fn new(_ctx: &mut Context, world: &mut World) -> Self {
+        let settings = (*world.fetch::<Settings>()).clone();
+        let (theme_names, theme) = {
+            let themes = world.fetch::<Themes>();
+            let names = themes.list.iter().map(|t| t.to_string()).collect();
+            (names, themes.position(&settings.theme))
+        };
+
+        let mut menu = Menu::new("options".to_string());
+        menu.add_setting(
+            Action::Theme,
+            "theme".to_string(),
+            10.0,
+            ItemKind::Choice {
+                options: theme_names,
+                selected: theme,
+            },
+        );
+        menu.add_setting(
+            Action::Control,
+            "control".to_string(),
+            10.0,
+            ItemKind::Choice {
+                options: CONTROLS.iter().map(|c| c.to_string()).collect(),
+                selected: CONTROLS
+                    .iter()
+                    .position(|c| *c == settings.control)
+                    .unwrap_or(0),
+            },
+        );
+        menu.add_setting(
+            Action::EnemyPattern,
+            "enemy pattern".to_string(),
+            10.0,
+            ItemKind::Toggle(settings.enemy_pattern),
+        );
+        menu.add_setting(
+            Action::RingPitch,
+            "ring pitch".to_string(),
+            10.0,
+            ItemKind::Toggle(settings.ring_pitch),
+        );
+        let volumes = [
+            (Action::MasterVolume, "volume", settings.mixer.master),
+            (Action::MusicVolume, "music", settings.mixer.music),
+            (Action::SfxVolume, "effects", settings.mixer.sfx),
+        ];
+        for (action, text, value) in volumes {
+            menu.add_setting(
+                action,
+                text.to_string(),
+                10.0,
+                ItemKind::Slider {
+                    value,
+                    min: 0.0,
+                    max: 1.0,
+                    step: VOLUME_STEP,
+                },
+            );
+        }
+        menu.add_setting(
+            Action::GameSpeed,
+            "game speed".to_string(),
+            10.0,
+            ItemKind::Slider {
+                value: settings.game_speed,
+                min: GAME_SPEED_MIN,
+                max: GAME_SPEED_MAX,
+                step: GAME_SPEED_STEP,
+            },
+        );
+        menu.add_setting(
+            Action::Fullscreen,
+            "fullscreen".to_string(),
+            10.0,
+            ItemKind::Toggle(settings.fullscreen),
+        );
+        menu.add_item(Action::Keys, "keys".to_string(), 10.0, true);
+        menu.add_item(Action::Back, "back".to_string(), 30.0, true);
+        menu.description = "[left/right] change".to_string();
+
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(UpdateMenu, "menu_system", &[])
+            .build();
+        dispatcher.setup(world);
+        Self {
+            dispatcher,
+            menu: Some(menu),
+        }
+    }
+
+    /// Copy the values shown in the menu into `Settings`.
+    fn apply(world: &mut World) {
+        let mut next = (*world.fetch::<Settings>()).clone();
+        {
+            let menu = world.fetch::<Menu>();
+            for item in &menu.items {
+                match (&item.action, &item.kind) {
+                    (Action::Theme, ItemKind::Choice { selected, .. }) => {
+                        if let Some(theme) = world.fetch::<Themes>().list.get(*selected) {
+                            next.theme = theme.name.clone();
+                        }
+                    }
+                    (Action::Control, ItemKind::Choice { selected, .. }) => {
+                        next.control = CONTROLS[*selected].clone();
+                    }
+                    (Action::MasterVolume, ItemKind::Slider { value, .. }) => {
+                        next.mixer.master = *value
+                    }
+                    (Action::MusicVolume, ItemKind::Slider { value, .. }) => {
+                        next.mixer.music = *value
+                    }
+                    (Action::SfxVolume, ItemKind::Slider { value, .. }) => next.mixer.sfx = *value,
+                    (Action::GameSpeed, ItemKind::Slider { value, .. }) => next.game_speed = *value,
+                    (Action::Fullscreen, ItemKind::Toggle(on)) => next.fullscreen = *on,
+                    (Action::EnemyPattern, ItemKind::Toggle(on)) => next.enemy_pattern = *on,
+                    (Action::RingPitch, ItemKind::Toggle(on)) => next.ring_pitch = *on,
+                    _ => {}
+                }
+            }
+        }
+
+        let mut settings = world.fetch_mut::<Settings>();
+        if next != *settings {
+            next.changed = true;
+            next.apply(
+                &mut world.fetch_mut::<GameState>(),
+                &world.fetch::<Themes>(),
+            );
+            *settings = next;
+        }
+    }
+}
+
+impl<'a, 'b> Scene for OptionsScene<'a, 'b> {
+    fn update(&mut self, _ctx: &mut Context, world: &mut World) -> SceneResult {
+        self.dispatcher.dispatch(world);
+        OptionsScene::apply(world);
+
+        Ok(Transition::None)
+    }
+
+    fn draw(&mut self, _ctx: &mut Context, world: &mut World, canvas: &mut Canvas) -> GameResult {
+        let mut render = MenuRender::new(canvas);
+        render.run_now(world);
+        Ok(())
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        _input: KeyInput,
+        _repeat: bool,
+        world: &mut World,
+    ) -> SceneResult {
+        let pressed = world.fetch::<Bindings>().find(
+            &world.fetch::<KeyState>(),
+            &[InputAction::Confirm, InputAction::Back],
+        );
+        match pressed {
+            Some(InputAction::Confirm) => {
+                let action = world.fetch::<Menu>().get_currect_action();
+                match action {
+                    Action::Keys => Ok(Transition::Push(Box::new(KeysScene::new(ctx, world)))),
+                    Action::Back => Ok(Transition::Pop),
+                    _ => {
+                        world.fetch_mut::<Menu>().adjust_current(1);
+                        Ok(Transition::None)
+                    }
+                }
+            }
+            Some(InputAction::Back) => Ok(Transition::Pop),
+            _ => Ok(Transition::None),
+        }
+    }
+
+    fn menu(&mut self) -> Option<&mut Option<Menu>> {
+        Some(&mut self.menu)
+    }
+
+    fn name(&self) -> &str {
+        "Options"
+    }
+}
+
+impl<'a, 'b> fmt::Debug for OptionsScene<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -1,45 +1,49 @@
 use std::fmt;
 
 use ggez::graphics::Canvas;
-use ggez::input::keyboard::KeyCode;
 use ggez::input::keyboard::KeyInput;
 use ggez::{Context, GameResult};
 use specs::prelude::*;
 
-use crate::ecs::resources::{Action, Menu};
+use crate::ecs::resources::{Action, KeyState, Menu};
 use crate::ecs::systems::{MenuRender, UpdateGlobalState, UpdateMenu};
-use crate::scenes::stack::{Scene, Transition};
+use crate::input::{Bindings, InputAction};
+use crate::scenes::options::OptionsScene;
+use crate::scenes::stack::{Scene, SceneResult, Transition};
 
 pub struct PauseScene<'a, 'b> {
     dispatcher: Dispatcher<'a, 'b>,
+    menu: Option<Menu>,
 }
 
 impl<'a, 'b> PauseScene<'a, 'b> {
     pub fn new(_ctx: &mut Context, world: &mut World) -> Self {
         let mut menu = Menu::new("pause".to_string());
         menu.add_item(Action::Continue, "continue".to_string(), 10.0, true);
+        menu.add_item(Action::Options, "options".to_string(), 10.0, true);
         menu.add_item(Action::Quit, "quit".to_string(), 60.0, true);
 
-        world.insert(menu);
-
         let mut dispatcher = DispatcherBuilder::new()
             .with(UpdateGlobalState, "global_system", &[])
             .with(UpdateMenu, "menu_system", &[])
             .build();
         dispatcher.setup(world);
-        Self { dispatcher }
+        Self {
+            dispatcher,
+            menu: Some(menu),
+        }
     }
 }
 
 impl<'a, 'b> Scene for PauseScene<'a, 'b> {
-    fn update(&mut self, _ctx: &mut Context, world: &mut World) -> Result<Transition, String> {
+    fn update(&mut self, _ctx: &mut Context, world: &mut World) -> SceneResult {
         self.dispatcher.dispatch(world);
 
         Ok(Transition::None)
     }
 
-    fn draw(&mut self, ctx: &mut Context, world: &mut World, canvas: &mut Canvas) -> GameResult {
-        let mut render = MenuRender::new(ctx, canvas);
+    fn draw(&mut self, _ctx: &mut Context, world: &mut World, canvas: &mut Canvas) -> GameResult {
+        let mut render = MenuRender::new(canvas);
         render.run_now(world);
         Ok(())
     }
@@ -47,15 +51,22 @@ impl<'a, 'b> Scene for PauseScene<'a, 'b> {
     fn key_down_event(
         &mut self,
         ctx: &mut Context,
-        input: KeyInput,
+        _input: KeyInput,
         _repeat: bool,
         world: &mut World,
-    ) -> Result<Transition, String> {
-        match input.keycode {
-            Some(KeyCode::Return) => {
+    ) -> SceneResult {
+        let pressed = world.fetch::<Bindings>().find(
+            &world.fetch::<KeyState>(),
+            &[InputAction::Confirm, InputAction::Back],
+        );
+        match pressed {
+            Some(InputAction::Confirm) => {
                 let action = world.fetch::<Menu>().get_currect_action();
                 match action {
                     Action::Continue => Ok(Transition::Pop),
+                    Action::Options => {
+                        Ok(Transition::Push(Box::new(OptionsScene::new(ctx, world))))
+                    }
                     Action::Quit => {
                         ctx.request_quit();
                         Ok(Transition::None)
@@ -63,11 +74,15 @@ impl<'a, 'b> Scene for PauseScene<'a, 'b> {
                     _ => Ok(Transition::None),
                 }
             }
-            Some(KeyCode::Escape) => Ok(Transition::Pop),
+            Some(InputAction::Back) => Ok(Transition::Pop),
             _ => Ok(Transition::None),
         }
     }
 
+    fn menu(&mut self) -> Option<&mut Option<Menu>> {
+        Some(&mut self.menu)
+    }
+
     fn name(&self) -> &str {
         "Pause"
     }
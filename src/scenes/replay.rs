@@ -0,0 +1,123 @@
+use std::fmt;
+
+use ggez::graphics::Canvas;
+use ggez::input::keyboard::KeyInput;
+use ggez::{Context, GameResult};
+use log::warn;
+use specs::prelude::*;
+
+use crate::ecs::resources::{GameState, KeyState, Story};
+use crate::ecs::systems::GameRender;
+use crate::input::{Bindings, InputAction};
+use crate::replay::{Replay, Tick, LAST_PATH};
+use crate::scenes::stack::{Scene, SceneResult, Transition};
+use crate::sim::Headless;
+use crate::soundtrack::Track;
+use crate::storage;
+use crate::utils::{Control, Mode};
+
+/// Plays a recorded run in its own world, leaving the game untouched.
+pub struct ReplayScene<'a, 'b> {
+    sim: Headless<'a, 'b>,
+    ticks: Vec<(Control, Tick)>,
+    next: usize,
+}
+
+impl<'a, 'b> ReplayScene<'a, 'b> {
+    pub fn new(world: &mut World, replay: &Replay, ticks: Vec<(Control, Tick)>) -> Self {
+        let story = (*world.fetch::<Story>()).clone();
+        let sim = Headless::from_replay(replay, story);
+        sim.world.fetch_mut::<GameState>().theme = world.fetch::<GameState>().theme.clone();
+
+        Self {
+            sim,
+            ticks,
+            next: 0,
+        }
+    }
+
+    /// Push a scene playing the last recorded run, if it can be read.
+    pub fn open_last(ctx: &mut Context, world: &mut World) -> Transition {
+        let replay = match storage::read::<Replay>(ctx, LAST_PATH) {
+            Ok(replay) => replay,
+            Err(e) => {
+                warn!("Can't load replay: {}", e);
+                return Transition::None;
+            }
+        };
+        if let Mode::Story(chapter) = replay.mode {
+            if world.fetch::<Story>().chapter(chapter).is_none() {
+                warn!("Can't play replay: chapter {} isn't loaded", chapter + 1);
+                return Transition::None;
+            }
+        }
+        if !replay.is_current_version() {
+            warn!(
+                "Replay was recorded with version {}, it may play differently",
+                replay.version
+            );
+        }
+
+        match replay.decode() {
+            Ok(ticks) => Transition::Push(Box::new(ReplayScene::new(world, &replay, ticks))),
+            Err(e) => {
+                warn!("Can't play replay: {}", e);
+                Transition::None
+            }
+        }
+    }
+}
+
+impl<'a, 'b> Scene for ReplayScene<'a, 'b> {
+    fn update(&mut self, _ctx: &mut Context, _world: &mut World) -> SceneResult {
+        let (control, tick) = match self.ticks.get(self.next) {
+            Some(&(ref control, tick)) => (control.clone(), tick),
+            None => return Ok(Transition::Pop),
+        };
+        self.next += 1;
+
+        self.sim.world.fetch_mut::<GameState>().control = control;
+        if self.sim.tick(tick).is_some() {
+            return Ok(Transition::Pop);
+        }
+
+        Ok(Transition::None)
+    }
+
+    fn draw(&mut self, ctx: &mut Context, _world: &mut World, canvas: &mut Canvas) -> GameResult {
+        let mut rrs = GameRender::new(ctx, canvas);
+        rrs.run_now(&self.sim.world);
+        Ok(())
+    }
+
+    fn key_down_event(
+        &mut self,
+        _ctx: &mut Context,
+        _input: KeyInput,
+        _repeat: bool,
+        world: &mut World,
+    ) -> SceneResult {
+        let pressed = world.fetch::<Bindings>().find(
+            &world.fetch::<KeyState>(),
+            &[InputAction::Confirm, InputAction::Back],
+        );
+        match pressed {
+            Some(InputAction::Confirm) | Some(InputAction::Back) => Ok(Transition::Pop),
+            _ => Ok(Transition::None),
+        }
+    }
+
+    fn track(&self) -> Option<Track> {
+        Some(Track::Game)
+    }
+
+    fn name(&self) -> &str {
+        "Replay"
+    }
+}
+
+impl<'a, 'b> fmt::Debug for ReplayScene<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
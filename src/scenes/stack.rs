@@ -1,13 +1,51 @@
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
 
+use ggez::event::{Axis, Button, MouseButton};
 use ggez::graphics::Canvas;
 use ggez::input::keyboard::KeyInput;
-use ggez::{Context, GameResult};
-use log::warn;
+use ggez::{Context, GameError, GameResult};
+use log::{error, warn};
 use specs::World;
 
+use crate::ecs::resources::{KeyState, Menu};
+use crate::input::{self, InputAction};
+use crate::scenes::error::ErrorScene;
+use crate::soundtrack::{Soundtrack, Track};
+
+/// Failure of a scene, shown on an error screen instead of crashing the game.
+#[derive(Debug)]
+pub enum SceneError {
+    Game(GameError),
+    Message(String),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Game(e) => write!(f, "{}", e),
+            SceneError::Message(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<GameError> for SceneError {
+    fn from(e: GameError) -> Self {
+        SceneError::Game(e)
+    }
+}
+
+impl From<String> for SceneError {
+    fn from(msg: String) -> Self {
+        SceneError::Message(msg)
+    }
+}
+
+pub type SceneResult<T = Transition> = Result<T, SceneError>;
+
 pub trait Scene: Debug {
-    fn update(&mut self, ctx: &mut Context, world: &mut World) -> Result<Transition, String>;
+    fn update(&mut self, ctx: &mut Context, world: &mut World) -> SceneResult;
     fn draw(&mut self, ctx: &mut Context, world: &mut World, canvas: &mut Canvas) -> GameResult;
     fn key_down_event(
         &mut self,
@@ -15,7 +53,158 @@ pub trait Scene: Debug {
         input: KeyInput,
         repeat: bool,
         world: &mut World,
-    ) -> Result<Transition, String>;
+    ) -> SceneResult;
+    fn key_up_event(
+        &mut self,
+        _ctx: &mut Context,
+        _input: KeyInput,
+        _world: &mut World,
+    ) -> SceneResult {
+        Ok(Transition::None)
+    }
+    fn text_input_event(
+        &mut self,
+        _ctx: &mut Context,
+        _character: char,
+        _world: &mut World,
+    ) -> SceneResult {
+        Ok(Transition::None)
+    }
+    /// Menu scenes select the item under the pointer.
+    fn mouse_motion_event(
+        &mut self,
+        _ctx: &mut Context,
+        x: f32,
+        y: f32,
+        world: &mut World,
+    ) -> SceneResult {
+        if self.menu().is_some() {
+            world.fetch_mut::<Menu>().hover(x, y);
+        }
+        Ok(Transition::None)
+    }
+    /// Menu scenes confirm the item under the pointer, as if the confirm
+    /// key was pressed.
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut Context,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+        world: &mut World,
+    ) -> SceneResult {
+        if self.menu().is_none()
+            || button != MouseButton::Left
+            || !world.fetch_mut::<Menu>().hover(x, y)
+        {
+            return Ok(Transition::None);
+        }
+        world
+            .fetch_mut::<KeyState>()
+            .press(vec![InputAction::Confirm]);
+        self.key_down_event(ctx, input::action_input(), false, world)
+    }
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut Context,
+        _button: MouseButton,
+        _x: f32,
+        _y: f32,
+        _world: &mut World,
+    ) -> SceneResult {
+        Ok(Transition::None)
+    }
+    fn mouse_wheel_event(
+        &mut self,
+        _ctx: &mut Context,
+        _x: f32,
+        _y: f32,
+        _world: &mut World,
+    ) -> SceneResult {
+        Ok(Transition::None)
+    }
+    /// Gamepad buttons act like keys by default; their actions are already
+    /// in `KeyState`.
+    fn gamepad_button_down_event(
+        &mut self,
+        ctx: &mut Context,
+        _btn: Button,
+        world: &mut World,
+    ) -> SceneResult {
+        self.key_down_event(ctx, input::action_input(), false, world)
+    }
+    fn gamepad_button_up_event(
+        &mut self,
+        _ctx: &mut Context,
+        _btn: Button,
+        _world: &mut World,
+    ) -> SceneResult {
+        Ok(Transition::None)
+    }
+    fn gamepad_axis_event(
+        &mut self,
+        _ctx: &mut Context,
+        _axis: Axis,
+        _value: f32,
+        _world: &mut World,
+    ) -> SceneResult {
+        Ok(Transition::None)
+    }
+    fn focus_event(
+        &mut self,
+        _ctx: &mut Context,
+        _gained: bool,
+        _world: &mut World,
+    ) -> SceneResult {
+        Ok(Transition::None)
+    }
+    fn resize_event(
+        &mut self,
+        _ctx: &mut Context,
+        _width: f32,
+        _height: f32,
+        _world: &mut World,
+    ) -> SceneResult {
+        Ok(Transition::None)
+    }
+    /// Called when the scene is put on the stack. By default the scene's
+    /// menu goes into the world and its track starts.
+    fn on_enter(&mut self, _ctx: &mut Context, world: &mut World) -> SceneResult<()> {
+        if let Some(menu) = self.menu().and_then(Option::take) {
+            world.insert(menu);
+        }
+        play_track(self.track(), world);
+        Ok(())
+    }
+    /// Called when the scene is taken off the stack.
+    fn on_exit(&mut self, _ctx: &mut Context, _world: &mut World) -> SceneResult<()> {
+        Ok(())
+    }
+    /// Called when another scene is pushed on top of this one. By default
+    /// the scene takes its menu back from the world.
+    fn on_pause(&mut self, _ctx: &mut Context, world: &mut World) -> SceneResult<()> {
+        if let Some(menu) = self.menu() {
+            *menu = world.remove::<Menu>();
+        }
+        Ok(())
+    }
+    /// Called when the scene on top of this one is popped. By default it
+    /// does the same as `on_enter`.
+    fn on_resume(&mut self, ctx: &mut Context, world: &mut World) -> SceneResult<()> {
+        self.on_enter(ctx, world)
+    }
+    /// Menu of the scene, kept in the world only while the scene is on top.
+    fn menu(&mut self) -> Option<&mut Option<Menu>> {
+        None
+    }
+    /// Music played while the scene is on top; `None` keeps the current one.
+    fn track(&self) -> Option<Track> {
+        None
+    }
+    /// Whether every key goes to the scene, e.g. while one is being bound.
+    fn captures_keys(&self) -> bool {
+        false
+    }
     fn name(&self) -> &str;
     fn draw_previous(&self) -> bool {
         false
@@ -29,6 +218,8 @@ pub enum Transition {
     Pop,
     Replace(Box<dyn Scene>),
     MultiReplace(Vec<Box<dyn Scene>>, u32),
+    /// Take every scene off the stack and start over.
+    Reset(Box<dyn Scene>),
 }
 
 pub struct SceneStack {
@@ -36,26 +227,16 @@ pub struct SceneStack {
 }
 
 impl SceneStack {
-    pub fn new(scene: Box<dyn Scene>) -> Self {
-        Self {
-            scenes: vec![scene],
-        }
-    }
-
-    #[allow(dead_code)]
-    pub fn push(&mut self, scene: Box<dyn Scene>) {
-        self.scenes.push(scene)
-    }
-
-    #[allow(dead_code)]
-    pub fn pop(&mut self) {
-        self.scenes.pop();
+    pub fn new(ctx: &mut Context, world: &mut World, scene: Box<dyn Scene>) -> Self {
+        let mut stack = Self { scenes: vec![] };
+        let result = stack.enter(ctx, world, scene);
+        stack.apply(ctx, world, result.map(|_| Transition::None));
+        stack
     }
 
     pub fn update(&mut self, ctx: &mut Context, world: &mut World) -> GameResult {
-        let scene = self.mut_scene();
-        let trans = scene.update(ctx, world).unwrap();
-        self.switch(trans);
+        let result = self.mut_scene().and_then(|scene| scene.update(ctx, world));
+        self.apply(ctx, world, result);
         Ok(())
     }
 
@@ -65,7 +246,10 @@ impl SceneStack {
         world: &mut World,
         canvas: &mut Canvas,
     ) -> GameResult {
-        SceneStack::draw_scenes(&mut self.scenes, ctx, world, canvas)
+        if let Err(e) = SceneStack::draw_scenes(&mut self.scenes, ctx, world, canvas) {
+            self.fail(ctx, world, e.into());
+        }
+        Ok(())
     }
 
     fn draw_scenes(
@@ -74,7 +258,6 @@ impl SceneStack {
         world: &mut World,
         canvas: &mut Canvas,
     ) -> GameResult {
-        assert!(!scenes.is_empty());
         if let Some((current, rest)) = scenes.split_last_mut() {
             if current.draw_previous() {
                 SceneStack::draw_scenes(rest, ctx, world, canvas)?
@@ -91,42 +274,226 @@ impl SceneStack {
         repeat: bool,
         world: &mut World,
     ) {
-        let scene = self.mut_scene();
-        let trans = scene.key_down_event(ctx, input, repeat, world).unwrap();
-        self.switch(trans);
+        let result = self
+            .mut_scene()
+            .and_then(|scene| scene.key_down_event(ctx, input, repeat, world));
+        self.apply(ctx, world, result);
+    }
+
+    pub fn key_up_event(&mut self, ctx: &mut Context, input: KeyInput, world: &mut World) {
+        let result = self
+            .mut_scene()
+            .and_then(|scene| scene.key_up_event(ctx, input, world));
+        self.apply(ctx, world, result);
+    }
+
+    pub fn text_input_event(&mut self, ctx: &mut Context, character: char, world: &mut World) {
+        let result = self
+            .mut_scene()
+            .and_then(|scene| scene.text_input_event(ctx, character, world));
+        self.apply(ctx, world, result);
     }
 
-    fn mut_scene(&mut self) -> &mut dyn Scene {
-        &mut **self.scenes.last_mut().expect("Not scene in stack")
+    pub fn mouse_motion_event(&mut self, ctx: &mut Context, x: f32, y: f32, world: &mut World) {
+        let result = self
+            .mut_scene()
+            .and_then(|scene| scene.mouse_motion_event(ctx, x, y, world));
+        self.apply(ctx, world, result);
     }
 
-    fn switch(&mut self, trans: Transition) {
+    pub fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut Context,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+        world: &mut World,
+    ) {
+        let result = self
+            .mut_scene()
+            .and_then(|scene| scene.mouse_button_down_event(ctx, button, x, y, world));
+        self.apply(ctx, world, result);
+    }
+
+    pub fn mouse_button_up_event(
+        &mut self,
+        ctx: &mut Context,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+        world: &mut World,
+    ) {
+        let result = self
+            .mut_scene()
+            .and_then(|scene| scene.mouse_button_up_event(ctx, button, x, y, world));
+        self.apply(ctx, world, result);
+    }
+
+    pub fn mouse_wheel_event(&mut self, ctx: &mut Context, x: f32, y: f32, world: &mut World) {
+        let result = self
+            .mut_scene()
+            .and_then(|scene| scene.mouse_wheel_event(ctx, x, y, world));
+        self.apply(ctx, world, result);
+    }
+
+    pub fn gamepad_button_down_event(&mut self, ctx: &mut Context, btn: Button, world: &mut World) {
+        let result = self
+            .mut_scene()
+            .and_then(|scene| scene.gamepad_button_down_event(ctx, btn, world));
+        self.apply(ctx, world, result);
+    }
+
+    pub fn gamepad_button_up_event(&mut self, ctx: &mut Context, btn: Button, world: &mut World) {
+        let result = self
+            .mut_scene()
+            .and_then(|scene| scene.gamepad_button_up_event(ctx, btn, world));
+        self.apply(ctx, world, result);
+    }
+
+    pub fn gamepad_axis_event(
+        &mut self,
+        ctx: &mut Context,
+        axis: Axis,
+        value: f32,
+        world: &mut World,
+    ) {
+        let result = self
+            .mut_scene()
+            .and_then(|scene| scene.gamepad_axis_event(ctx, axis, value, world));
+        self.apply(ctx, world, result);
+    }
+
+    pub fn focus_event(&mut self, ctx: &mut Context, gained: bool, world: &mut World) {
+        let result = self
+            .mut_scene()
+            .and_then(|scene| scene.focus_event(ctx, gained, world));
+        self.apply(ctx, world, result);
+    }
+
+    pub fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32, world: &mut World) {
+        let result = self
+            .mut_scene()
+            .and_then(|scene| scene.resize_event(ctx, width, height, world));
+        self.apply(ctx, world, result);
+    }
+
+    pub fn captures_keys(&self) -> bool {
+        self.scenes
+            .last()
+            .is_some_and(|scene| scene.captures_keys())
+    }
+
+    fn mut_scene(&mut self) -> SceneResult<&mut dyn Scene> {
+        match self.scenes.last_mut() {
+            Some(scene) => Ok(&mut **scene),
+            None => Err(SceneError::Message("No scene in the stack".to_string())),
+        }
+    }
+
+    /// Run the transition asked for by a scene, or show its error.
+    fn apply(&mut self, ctx: &mut Context, world: &mut World, result: SceneResult) {
+        if let Err(e) = result.and_then(|trans| self.switch(ctx, world, trans)) {
+            self.fail(ctx, world, e);
+        }
+    }
+
+    fn fail(&mut self, ctx: &mut Context, world: &mut World, e: SceneError) {
+        let name = self.scenes.last().map_or("none", |scene| scene.name());
+        error!("Scene {} failed: {}", name, e);
+
+        // Hooks failing here are only logged, so an error can't cascade
+        if let Some(top) = self.scenes.last_mut() {
+            if let Err(e) = top.on_pause(ctx, world) {
+                error!("Scene {} failed to pause: {}", top.name(), e);
+            }
+        }
+        let scene = Box::new(ErrorScene::new(ctx, world, &e));
+        if let Err(e) = self.enter(ctx, world, scene) {
+            error!("Can't show error: {}", e);
+        }
+    }
+
+    fn switch(
+        &mut self,
+        ctx: &mut Context,
+        world: &mut World,
+        trans: Transition,
+    ) -> SceneResult<()> {
         match trans {
             Transition::Push(scene) => {
-                self.scenes.push(scene);
+                if let Some(top) = self.scenes.last_mut() {
+                    top.on_pause(ctx, world)?;
+                }
+                self.enter(ctx, world, scene)?;
             }
             Transition::Pop => {
-                if self.scenes.pop().is_none() {
-                    warn!("Stack doesn't have scene for pop");
-                }
+                self.exit(ctx, world, 1)?;
+                self.resume(ctx, world)?;
             }
             Transition::Replace(scene) => {
-                if self.scenes.pop().is_none() {
-                    warn!("Stack doesn't have scene for replace");
-                }
-                self.scenes.push(scene)
+                self.exit(ctx, world, 1)?;
+                self.enter(ctx, world, scene)?;
             }
-            Transition::MultiReplace(scenes, mut num) => {
-                while num > 0 {
-                    if self.scenes.pop().is_none() {
-                        warn!("Stack doesn't have scene for replace");
+            Transition::MultiReplace(scenes, num) => {
+                self.exit(ctx, world, num)?;
+                if scenes.is_empty() {
+                    self.resume(ctx, world)?;
+                }
+                // Every scene that gets covered pauses, the one that asked for
+                // the transition too when it stays. A scene uncovered by the
+                // exit already paused when it was covered.
+                for (i, scene) in scenes.into_iter().enumerate() {
+                    if i > 0 || num == 0 {
+                        if let Some(top) = self.scenes.last_mut() {
+                            top.on_pause(ctx, world)?;
+                        }
                     }
-                    num -= 1;
+                    self.enter(ctx, world, scene)?;
                 }
-
-                self.scenes.extend(scenes)
+            }
+            Transition::Reset(scene) => {
+                self.exit(ctx, world, self.scenes.len() as u32)?;
+                self.enter(ctx, world, scene)?;
             }
             Transition::None => {}
         };
+        if self.scenes.is_empty() {
+            return Err(SceneError::Message("The last scene was closed".to_string()));
+        }
+        Ok(())
+    }
+
+    fn enter(
+        &mut self,
+        ctx: &mut Context,
+        world: &mut World,
+        scene: Box<dyn Scene>,
+    ) -> SceneResult<()> {
+        // On the stack first, so a failing hook is reported against it
+        self.scenes.push(scene);
+        self.mut_scene()?.on_enter(ctx, world)
+    }
+
+    fn exit(&mut self, ctx: &mut Context, world: &mut World, num: u32) -> SceneResult<()> {
+        for _ in 0..num {
+            match self.scenes.pop() {
+                Some(mut scene) => scene.on_exit(ctx, world)?,
+                None => warn!("Stack doesn't have scene for pop"),
+            }
+        }
+        Ok(())
+    }
+
+    fn resume(&mut self, ctx: &mut Context, world: &mut World) -> SceneResult<()> {
+        if let Some(top) = self.scenes.last_mut() {
+            top.on_resume(ctx, world)?;
+        }
+        Ok(())
+    }
+}
+
+fn play_track(track: Option<Track>, world: &World) {
+    if let (Some(track), Some(mut soundtrack)) = (track, world.try_fetch_mut::<Soundtrack>()) {
+        soundtrack.play(track);
     }
 }
@@ -81,6 +81,37 @@ pub fn arc(
     points
 }
 
+/// Diagonal lines across an arc drawn by `arc` with `width_inner`,
+/// `step` apart along the outer edge.
+pub fn hatching(
+    radius: f32,
+    start_angle: f32,
+    radian_size: f32,
+    width: f32,
+    step: f32,
+) -> Vec<[mt::Point2<f32>; 2]> {
+    let inner = radius - width;
+    let skew = width / radius;
+    let angle_step = step / radius;
+
+    let mut lines = Vec::new();
+    let mut angle = start_angle;
+    while angle + skew <= start_angle + radian_size {
+        lines.push([
+            mt::Point2 {
+                x: angle.cos() * inner,
+                y: angle.sin() * inner,
+            },
+            mt::Point2 {
+                x: (angle + skew).cos() * radius,
+                y: (angle + skew).sin() * radius,
+            },
+        ]);
+        angle += angle_step;
+    }
+    lines
+}
+
 pub fn player(radius: f32, angle: f32, size: f32, width: f32) -> Vec<mt::Point2<f32>> {
     let x1 = angle.cos() * radius;
     let y1 = angle.sin() * radius;
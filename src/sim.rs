@@ -0,0 +1,406 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use ggez::input::keyboard::KeyCode;
+use ggez::{GameError, GameResult};
+use rand::Rng;
+use specs::prelude::*;
+
+use crate::consts::{HORIZONTAL_SPEED_MIN, PLAYER_LIFE};
+use crate::ecs::components::{ConstantMovement, Enemy, Form, Player, Position, View};
+use crate::ecs::resources::{Clock, Editor, GameState, GameTime, KeyState, Recorder, Seed, Story};
+use crate::ecs::systems::{Collision, UpdateClock, UpdateGameState, UpdatePosition, UpdateTimer};
+use crate::input::{Bindings, InputAction};
+use crate::levels::{Level, RingKind};
+use crate::replay::{Replay, Tick};
+use crate::utils::{GameStatus, Mode};
+
+pub fn register(world: &mut World) {
+    world.register::<Position>();
+    world.register::<View>();
+    world.register::<Player>();
+    world.register::<Enemy>();
+    world.register::<ConstantMovement>();
+}
+
+/// Systems that make up the gameplay loop, without rendering or audio.
+pub fn dispatcher<'a, 'b>() -> Dispatcher<'a, 'b> {
+    DispatcherBuilder::new()
+        .with(UpdateClock, "clock_system", &[])
+        .with(UpdateTimer, "time_system", &["clock_system"])
+        .with(UpdatePosition, "pos_system", &["clock_system"])
+        .with(Collision, "collision_system", &["pos_system"])
+        .with(UpdateGameState, "game_system", &["time_system"])
+        .build()
+}
+
+/// Begin a new run in `mode` and start recording its input.
+pub fn start_run(world: &mut World, mode: Mode) {
+    world.fetch_mut::<GameState>().reset_result();
+    world.fetch_mut::<Seed>().reroll();
+    continue_run(world, mode);
+}
+
+/// Go on with the current run in `mode`, keeping its score, and record
+/// the input from here on.
+pub fn continue_run(world: &mut World, mode: Mode) {
+    world.fetch_mut::<GameState>().mode = mode;
+
+    let game_level = world.fetch::<GameState>().game_level;
+    let seed = world.fetch::<Seed>().value;
+    world.fetch_mut::<Recorder>().start(mode, game_level, seed);
+}
+
+/// Build the level for the current mode and spawn its entities.
+pub fn start_level(world: &mut World) {
+    let game_level = world.fetch::<GameState>().game_level;
+    let mut rng = world.fetch::<Seed>().rng(game_level);
+    let level = match world.fetch::<GameState>().mode {
+        Mode::Endless => Level::endless(game_level, &mut rng),
+        Mode::Story(chapter) => world
+            .fetch::<Story>()
+            .chapter(chapter)
+            .cloned()
+            .expect("story runs only start on a loaded chapter"),
+        Mode::Test => world.fetch::<Editor>().level.clone(),
+    };
+
+    spawn_level(world, &level, &mut rng);
+}
+
+pub fn spawn_level<R: Rng>(world: &mut World, level: &Level, rng: &mut R) {
+    world.insert(Clock::default());
+    world.insert(GameTime::new(level.time));
+    world.insert(level.player);
+
+    world
+        .create_entity()
+        .with(Position::new(level.player.level, level.player.angle))
+        .with(View {
+            form: Form::Triangle,
+            size: 8.0,
+        })
+        .with(Player {
+            life: PLAYER_LIFE,
+            speed: HORIZONTAL_SPEED_MIN,
+            speed_press_ms: 0.0,
+            start_angle_repeat: level.player.angle,
+        })
+        .build();
+
+    for ring in &level.rings {
+        for &(angle, size) in &ring.segments {
+            let color = rng.gen_range(0..2);
+            let mut builder = world
+                .create_entity()
+                .with(Position::new(ring.level, angle))
+                .with(View {
+                    form: Form::Circle,
+                    size,
+                });
+
+            if ring.kind == RingKind::Enemy {
+                builder = builder.with(Enemy { color });
+            }
+            if let Some(movement) = ring.movement {
+                builder = builder.with(ConstantMovement {
+                    direction: movement.to_direction(),
+                });
+            }
+            builder.build();
+        }
+    }
+}
+
+/// Clear a finished level from the world and add its result to the run.
+pub fn finish_level(world: &mut World) -> Option<GameStatus> {
+    let status = world.fetch_mut::<GameState>().status.take()?;
+    let score = world.fetch::<GameTime>().timer.as_secs();
+
+    world.delete_all();
+    world.maintain();
+
+    let mut gs = world.fetch_mut::<GameState>();
+    if status == GameStatus::LevelCompleted && gs.mode != Mode::Test {
+        gs.game_level += 1;
+        gs.score += score;
+    }
+    Some(status)
+}
+
+/// Scripted input: hold `key` (or nothing) for the given number of ticks.
+#[derive(Debug, Default)]
+pub struct Script {
+    pub steps: Vec<(u32, Option<KeyCode>)>,
+}
+
+impl Script {
+    pub fn load(path: &Path) -> GameResult<Script> {
+        Script::parse(&fs::read_to_string(path)?)
+    }
+
+    /// One step per line, `<ticks> <key>`, where key is one of
+    /// `left`, `right`, `up`, `down` or `none`. `#` starts a comment.
+    pub fn parse(source: &str) -> GameResult<Script> {
+        let mut steps = vec![];
+        for (i, line) in source.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let error = || GameError::CustomError(format!("Bad script line {}: {}", i + 1, line));
+            let mut parts = line.split_whitespace();
+            let ticks = parts
+                .next()
+                .and_then(|t| t.parse().ok())
+                .ok_or_else(error)?;
+            let key = match parts.next() {
+                Some("left") => Some(KeyCode::Left),
+                Some("right") => Some(KeyCode::Right),
+                Some("up") => Some(KeyCode::Up),
+                Some("down") => Some(KeyCode::Down),
+                Some("none") | None => None,
+                Some(_) => return Err(error()),
+            };
+            steps.push((ticks, key));
+        }
+        Ok(Script { steps })
+    }
+}
+
+#[derive(Debug)]
+pub struct Outcome {
+    pub status: Option<GameStatus>,
+    pub game_level: u32,
+    pub score: u64,
+    pub ticks: u64,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let status = match self.status {
+            Some(GameStatus::GameOver) => "game over",
+            Some(GameStatus::LevelCompleted) => "completed",
+            None => "running",
+        };
+        write!(
+            f,
+            "status={} level={} score={} ticks={}",
+            status, self.game_level, self.score, self.ticks
+        )
+    }
+}
+
+/// Game world stepped without a ggez `Context`, window or audio device.
+pub struct Headless<'a, 'b> {
+    pub world: World,
+    dispatcher: Dispatcher<'a, 'b>,
+    last_key: Option<KeyCode>,
+    ticks: u64,
+}
+
+impl<'a, 'b> Headless<'a, 'b> {
+    /// Endless run from `seed`, or a single custom `level` when given.
+    pub fn new(seed: Seed, level: Option<Level>) -> Self {
+        let mut world = World::new();
+        register(&mut world);
+
+        let mut gs = GameState::default();
+        if let Some(level) = level {
+            gs.mode = Mode::Test;
+            world.insert(Editor {
+                level,
+                ..Default::default()
+            });
+        }
+        world.insert(gs);
+        world.insert(KeyState::default());
+        world.insert(Bindings::default());
+        world.insert(seed);
+
+        Headless::start(world)
+    }
+
+    /// Run recorded in `replay`; story chapters are taken from `story`.
+    pub fn from_replay(replay: &Replay, story: Story) -> Self {
+        let mut world = World::new();
+        register(&mut world);
+
+        world.insert(GameState {
+            game_level: replay.game_level,
+            mode: replay.mode,
+            ..Default::default()
+        });
+        world.insert(KeyState::default());
+        world.insert(Bindings::default());
+        world.insert(Seed::new(replay.seed));
+        world.insert(story);
+
+        Headless::start(world)
+    }
+
+    fn start(mut world: World) -> Self {
+        let mut dispatcher = dispatcher();
+        dispatcher.setup(&mut world);
+        start_level(&mut world);
+
+        Headless {
+            world,
+            dispatcher,
+            last_key: None,
+            ticks: 0,
+        }
+    }
+
+    /// Advance one tick with `key` held, treating it as repeated when it
+    /// was also held on the previous tick.
+    pub fn step(&mut self, key: Option<KeyCode>) -> Option<GameStatus> {
+        let mut ks = KeyState::default();
+        ks.held.extend(key);
+        if key != self.last_key {
+            ks.just_pressed.extend(key);
+        }
+        self.last_key = key;
+
+        let control = self.world.fetch::<GameState>().control.clone();
+        let tick = Tick::from_key_state(&control, &ks, &self.world.fetch::<Bindings>());
+        self.tick(tick)
+    }
+
+    /// Advance one tick with the given input, starting the next level when
+    /// an endless one is completed.
+    pub fn tick(&mut self, tick: Tick) -> Option<GameStatus> {
+        {
+            let bindings = self.world.fetch::<Bindings>();
+            let key = |action: Option<InputAction>| {
+                action.and_then(|action| bindings.keys(action).first().copied())
+            };
+
+            let mut ks = self.world.fetch_mut::<KeyState>();
+            ks.held = key(tick.rotation)
+                .into_iter()
+                .chain(key(tick.step))
+                .collect();
+            ks.just_pressed.clear();
+            if !tick.repeat {
+                ks.just_pressed.extend(key(tick.action()));
+            }
+            ks.stick = tick.stick();
+            ks.aim = tick.aim();
+        }
+
+        self.dispatcher.dispatch(&self.world);
+        self.world.maintain();
+        self.ticks += 1;
+
+        let status = finish_level(&mut self.world);
+        if status == Some(GameStatus::LevelCompleted)
+            && self.world.fetch::<GameState>().mode == Mode::Endless
+        {
+            start_level(&mut self.world);
+            return None;
+        }
+        status
+    }
+
+    /// Play the script, stopping early when the run ends.
+    pub fn run(&mut self, script: &Script) -> Outcome {
+        let mut status = None;
+        'script: for &(ticks, key) in &script.steps {
+            for _ in 0..ticks {
+                status = self.step(key);
+                if status.is_some() {
+                    break 'script;
+                }
+            }
+        }
+
+        let gs = self.world.fetch::<GameState>();
+        Outcome {
+            status,
+            game_level: gs.game_level,
+            score: gs.score,
+            ticks: self.ticks,
+        }
+    }
+}
+
+pub fn load_level(path: &Path) -> GameResult<Level> {
+    toml::from_str(&fs::read_to_string(path)?)
+        .map_err(|e| GameError::ResourceLoadError(format!("Can't parse {:?}: {}", path, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consts::{DESIRED_FPS, GAME_TIME, TIMER_LOW};
+    use crate::ecs::events::{GameEvent, GameEvents};
+
+    #[test]
+    fn parse_script() {
+        let script = Script::parse("# warm up\n30\n\n200 up # go\n5 left\n1 none\n").unwrap();
+        assert_eq!(
+            script.steps,
+            vec![
+                (30, None),
+                (200, Some(KeyCode::Up)),
+                (5, Some(KeyCode::Left)),
+                (1, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_bad_script() {
+        assert!(Script::parse("ten up").is_err());
+        assert!(Script::parse("10 jump").is_err());
+        assert!(Script::parse("-1 up").is_err());
+    }
+
+    #[test]
+    fn idle_run_times_out() {
+        let script = Script::parse("10000 none").unwrap();
+        let outcome = Headless::new(Seed::new(42), None).run(&script);
+        assert_eq!(outcome.status, Some(GameStatus::GameOver));
+        assert_eq!(outcome.game_level, 1);
+        assert_eq!(outcome.score, 0);
+        assert_eq!(outcome.ticks, GAME_TIME * DESIRED_FPS as u64);
+    }
+
+    #[test]
+    fn empty_level_completes() {
+        let script = Script::parse("1000 up").unwrap();
+        let outcome = Headless::new(Seed::new(42), Some(Level::default())).run(&script);
+        assert_eq!(outcome.status, Some(GameStatus::LevelCompleted));
+        assert!(outcome.ticks < 1000);
+    }
+
+    #[test]
+    fn short_level_warns_once() {
+        let level = Level {
+            time: TIMER_LOW - 1,
+            ..Default::default()
+        };
+        let mut sim = Headless::new(Seed::new(42), Some(level));
+        let mut reader = sim.world.fetch_mut::<GameEvents>().register_reader();
+        for _ in 0..DESIRED_FPS {
+            sim.step(None);
+        }
+        let events = sim.world.fetch::<GameEvents>();
+        let warnings = events
+            .read(&mut reader)
+            .filter(|&&event| event == GameEvent::TimerLow)
+            .count();
+        assert_eq!(warnings, 1);
+    }
+
+    #[test]
+    fn same_seed_same_run() {
+        let script = Script::parse("30 none\n40 left\n200 up\n60 right\n200 up").unwrap();
+        let first = Headless::new(Seed::new(7), None).run(&script);
+        let second = Headless::new(Seed::new(7), None).run(&script);
+        assert_eq!(first.to_string(), second.to_string());
+    }
+}
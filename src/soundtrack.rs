@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use ggez::Context;
+use log::{debug, warn};
+use rodio::{OutputStreamHandle, Sink};
+
+use crate::audio::Clip;
+use crate::consts::{CROSSFADE_TIME, DUCK_TIME, DUCK_VOLUME};
+use crate::mixer::{Bus, Mixer};
+
+pub const MUSIC_DIR: &str = "/music";
+
+/// Looping background track; scenes pick one with `Scene::track`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Track {
+    Menu,
+    Game,
+    GameOver,
+}
+
+impl Track {
+    const ALL: [Track; 3] = [Track::Menu, Track::Game, Track::GameOver];
+
+    fn path(self) -> String {
+        let name = match self {
+            Track::Menu => "menu",
+            Track::Game => "game",
+            Track::GameOver => "gameover",
+        };
+        format!("{}/{}.flac", MUSIC_DIR, name)
+    }
+}
+
+struct Channel {
+    clip: Clip,
+    /// Playing while the track is heard, dropped to stop it.
+    sink: Option<Sink>,
+    /// Fade level, moved toward `target` by the crossfade.
+    gain: f32,
+    target: f32,
+}
+
+/// Background music. Tracks crossfade when the scene changes and are
+/// ducked while a sound effect plays. Tracks are optional and missing ones
+/// are skipped; without an output device nothing is loaded at all.
+#[derive(Default)]
+pub struct Soundtrack {
+    output: Option<OutputStreamHandle>,
+    channels: HashMap<Track, Channel>,
+    current: Option<Track>,
+    /// Seconds left until ducked music is back to full volume.
+    duck: f32,
+}
+
+impl Soundtrack {
+    pub fn load(ctx: &Context, output: Option<OutputStreamHandle>) -> Self {
+        let mut channels = HashMap::new();
+        if output.is_some() {
+            for track in Track::ALL {
+                // Music is optional, only a file that's there and broken is worth a warning
+                if !ctx.fs.exists(track.path()) {
+                    continue;
+                }
+                if let Some(clip) = Clip::load(ctx, &track.path()) {
+                    let channel = Channel {
+                        clip,
+                        sink: None,
+                        gain: 0.0,
+                        target: 0.0,
+                    };
+                    channels.insert(track, channel);
+                }
+            }
+        }
+
+        if output.is_some() && channels.is_empty() {
+            debug!("No music in {}", MUSIC_DIR);
+        }
+
+        Soundtrack {
+            output,
+            channels,
+            ..Default::default()
+        }
+    }
+
+    /// Fade over to `track`, fading out the others.
+    pub fn play(&mut self, track: Track) {
+        if self.current == Some(track) {
+            return;
+        }
+        self.current = Some(track);
+        for (t, channel) in self.channels.iter_mut() {
+            channel.target = if *t == track { 1.0 } else { 0.0 };
+        }
+    }
+
+    /// Lower the music for a moment, so a sound effect stands out.
+    pub fn duck(&mut self) {
+        self.duck = DUCK_TIME;
+    }
+
+    /// Advance fades by `dt` seconds and set the volume of every track.
+    pub fn update(&mut self, dt: f32, mixer: &Mixer) {
+        let output = match &self.output {
+            Some(output) => output,
+            None => return,
+        };
+        let volume = mixer.volume(Bus::Music);
+        self.duck = (self.duck - dt).max(0.0);
+        // Back to full volume linearly as the duck runs out
+        let duck = 1.0 - (1.0 - DUCK_VOLUME) * self.duck / DUCK_TIME;
+
+        let step = dt / CROSSFADE_TIME;
+        for channel in self.channels.values_mut() {
+            if channel.gain < channel.target {
+                if channel.sink.is_none() {
+                    match channel.clip.play_loop(output, 0.0) {
+                        Ok(sink) => channel.sink = Some(sink),
+                        Err(e) => warn!("Can't play music: {}", e),
+                    }
+                }
+                channel.gain = (channel.gain + step).min(channel.target);
+            } else if channel.gain > channel.target {
+                channel.gain = (channel.gain - step).max(channel.target);
+                if channel.gain == 0.0 {
+                    channel.sink = None;
+                }
+            }
+            if let Some(sink) = &channel.sink {
+                sink.set_volume(channel.gain * duck * volume);
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::fs;
+use std::io::{Read, Write};
+
+use ggez::{Context, GameError, GameResult};
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub fn read<T: DeserializeOwned>(ctx: &Context, path: &str) -> GameResult<T> {
+    let mut buffer = String::new();
+    ctx.fs.open(path)?.read_to_string(&mut buffer)?;
+
+    toml::from_str(&buffer)
+        .map_err(|e| GameError::ResourceLoadError(format!("Can't parse {}: {}", path, e)))
+}
+
+pub fn load<T: DeserializeOwned + Default>(ctx: &Context, path: &str) -> T {
+    if !ctx.fs.exists(path) {
+        return T::default();
+    }
+
+    read(ctx, path).unwrap_or_else(|e| {
+        warn!("Can't load {}: {}", path, e);
+        T::default()
+    })
+}
+
+/// toml writes plain values before tables and fails on a value that comes
+/// after one, so struct fields that serialize as tables, like nested
+/// structs or `Mode::Story`, have to be declared last.
+fn to_toml<T: Serialize>(path: &str, value: &T) -> GameResult<String> {
+    toml::to_string(value)
+        .map_err(|e| GameError::CustomError(format!("Can't serialize {}: {}", path, e)))
+}
+
+pub fn write<T: Serialize>(ctx: &Context, path: &str, value: &T) -> GameResult {
+    let data = to_toml(path, value)?;
+
+    ctx.fs.create(path)?.write_all(data.as_bytes())?;
+    Ok(())
+}
+
+/// Write to the user data directory, which ggez mounts read-only.
+pub fn write_data<T: Serialize>(ctx: &Context, path: &str, value: &T) -> GameResult {
+    let data = to_toml(path, value)?;
+
+    let dir = ctx.fs.user_data_dir();
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(path.trim_start_matches('/')), data)?;
+    Ok(())
+}
+
+pub fn save<T: Serialize>(ctx: &Context, path: &str, value: &T) {
+    if let Err(e) = write(ctx, path, value) {
+        warn!("Can't save {}: {}", path, e);
+    }
+}
+
+pub fn save_data<T: Serialize>(ctx: &Context, path: &str, value: &T) {
+    if let Err(e) = write_data(ctx, path, value) {
+        warn!("Can't save {}: {}", path, e);
+    }
+}
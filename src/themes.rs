@@ -0,0 +1,71 @@
+use ggez::Context;
+use log::warn;
+
+use crate::storage;
+use crate::utils::Theme;
+
+pub const THEMES_DIR: &str = "/themes";
+
+/// Every theme found in `themes/`, sorted by name.
+#[derive(Debug, Default)]
+pub struct Themes {
+    pub list: Vec<Theme>,
+}
+
+impl Themes {
+    /// Load every `.toml` file in `themes/`, skipping broken files.
+    /// Falls back to the built-in dark theme when none loads.
+    pub fn load(ctx: &Context) -> Self {
+        let paths = match ctx.fs.read_dir(THEMES_DIR) {
+            Ok(paths) => paths,
+            Err(e) => {
+                warn!("Can't load themes: {}", e);
+                return Themes {
+                    list: vec![Theme::default()],
+                };
+            }
+        };
+
+        let mut list: Vec<Theme> = paths
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .filter_map(|path| {
+                let name = path.file_stem()?.to_string_lossy().to_string();
+                match storage::read::<Theme>(ctx, &path.to_string_lossy()) {
+                    Ok(theme) => Some(Theme { name, ..theme }),
+                    Err(e) => {
+                        warn!("Can't load theme {}: {}", name, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        if list.is_empty() {
+            list.push(Theme::default());
+        }
+
+        Themes { list }
+    }
+
+    /// Theme called `name`, or the first one when there is no such theme.
+    pub fn get(&self, name: &str) -> Theme {
+        self.list
+            .iter()
+            .find(|t| t.name == name)
+            .or_else(|| self.list.first())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Theme after the one called `name`, wrapping around.
+    pub fn next(&self, name: &str) -> Theme {
+        match self.list.iter().position(|t| t.name == name) {
+            Some(i) => self.list[(i + 1) % self.list.len()].clone(),
+            None => self.get(name),
+        }
+    }
+
+    pub fn position(&self, name: &str) -> usize {
+        self.list.iter().position(|t| t.name == name).unwrap_or(0)
+    }
+}
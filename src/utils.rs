@@ -1,12 +1,14 @@
 use std::f32::consts::PI;
 use std::fmt;
+use std::time::SystemTime;
 
-use ggez::graphics::{self, Color};
-use ggez::input::keyboard::KeyCode;
-use rand::prelude::*;
+use ggez::graphics::Color;
 use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::consts::{FINAL_RADIUS, LEVEL_RADIUS, PI_2};
+use crate::input::InputAction;
 
 #[derive(Debug, Clone, Copy)]
 pub enum Direction {
@@ -45,168 +47,77 @@ pub enum Colour {
 
 impl Colour {
     pub fn value(&self, t: &Theme) -> Color {
-        match t {
-            Theme::Dark => match self {
-                Colour::White => graphics::Color::WHITE,
-                Colour::Bg => Color {
-                    r: 0.12,
-                    g: 0.14,
-                    b: 0.19,
-                    a: 1.0,
-                },
-                Colour::Fg => Color {
-                    r: 0.8,
-                    g: 0.8,
-                    b: 0.78,
-                    a: 1.0,
-                },
-                Colour::Gray => Color {
-                    r: 0.36,
-                    g: 0.4,
-                    b: 0.45,
-                    a: 1.0,
-                },
-                Colour::Special => Color {
-                    r: 1.0,
-                    g: 0.9,
-                    b: 0.7,
-                    a: 1.0,
-                },
-                Colour::Border => Color {
-                    r: 0.44,
-                    g: 0.48,
-                    b: 0.55,
-                    a: 1.0,
-                },
-                Colour::Life => Color {
-                    r: 0.73,
-                    g: 0.9,
-                    b: 0.49,
-                    a: 1.0,
-                },
-                Colour::LifeM => Color {
-                    r: 1.0,
-                    g: 0.65,
-                    b: 0.35,
-                    a: 1.0,
-                },
-                Colour::LifeL => Color {
-                    r: 1.0,
-                    g: 0.2,
-                    b: 0.2,
-                    a: 1.0,
-                },
-                Colour::Player => Color {
-                    r: 1.0,
-                    g: 0.8,
-                    b: 0.4,
-                    a: 1.0,
-                },
-                Colour::Enemy => Color {
-                    r: 0.95,
-                    g: 0.53,
-                    b: 0.47,
-                    a: 1.0,
-                },
-                Colour::EnemyA => Color {
-                    r: 1.0,
-                    g: 0.84,
-                    b: 0.50,
-                    a: 1.0,
-                },
-            },
-            Theme::Light => match self {
-                Colour::White => graphics::Color::BLACK,
-                Colour::Bg => Color {
-                    r: 0.98,
-                    g: 0.98,
-                    b: 0.98,
-                    a: 1.0,
-                },
-                Colour::Fg => Color {
-                    r: 0.42,
-                    g: 0.46,
-                    b: 0.50,
-                    a: 1.0,
-                },
-                Colour::Gray => Color {
-                    r: 0.67,
-                    g: 0.69,
-                    b: 0.71,
-                    a: 1.0,
-                },
-                Colour::Special => Color {
-                    r: 0.90,
-                    g: 0.73,
-                    b: 0.49,
-                    a: 1.0,
-                },
-                Colour::Border => Color {
-                    r: 0.58,
-                    g: 0.62,
-                    b: 0.65,
-                    a: 1.0,
-                },
-                Colour::Life => Color {
-                    r: 0.50,
-                    g: 0.70,
-                    b: 0.0,
-                    a: 1.0,
-                },
-                Colour::LifeM => Color {
-                    r: 0.98,
-                    g: 0.55,
-                    b: 0.24,
-                    a: 1.0,
-                },
-                Colour::LifeL => Color {
-                    r: 0.96,
-                    g: 0.09,
-                    b: 0.09,
-                    a: 1.0,
-                },
-                Colour::Player => Color {
-                    r: 1.0,
-                    g: 0.60,
-                    b: 0.25,
-                    a: 1.0,
-                },
-                Colour::Enemy => Color {
-                    r: 0.94,
-                    g: 0.44,
-                    b: 0.44,
-                    a: 1.0,
-                },
-                Colour::EnemyA => Color {
-                    r: 0.95,
-                    g: 0.68,
-                    b: 0.29,
-                    a: 1.0,
-                },
-            },
-        }
+        let [r, g, b] = match self {
+            Colour::Bg => t.bg,
+            Colour::Fg => t.fg,
+            Colour::Gray => t.gray,
+            Colour::Special => t.special,
+            Colour::Border => t.border,
+            Colour::Life => t.life,
+            Colour::LifeM => t.life_medium,
+            Colour::LifeL => t.life_low,
+            Colour::Player => t.player,
+            Colour::Enemy => t.enemy,
+            Colour::EnemyA => t.enemy_alt,
+            Colour::White => t.white,
+        };
+        Color::new(r, g, b, 1.0)
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Theme {
-    Light,
-    Dark,
+/// Colour of every `Colour` as `[r, g, b]`, loaded from a file in
+/// `themes/` named after the theme.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    #[serde(skip)]
+    pub name: String,
+    pub bg: [f32; 3],
+    pub fg: [f32; 3],
+    pub gray: [f32; 3],
+    pub special: [f32; 3],
+    pub border: [f32; 3],
+    pub life: [f32; 3],
+    pub life_medium: [f32; 3],
+    pub life_low: [f32; 3],
+    pub player: [f32; 3],
+    pub enemy: [f32; 3],
+    pub enemy_alt: [f32; 3],
+    pub white: [f32; 3],
+}
+
+impl Default for Theme {
+    /// The dark theme, used until the theme files are loaded.
+    fn default() -> Self {
+        Theme {
+            name: "dark".to_string(),
+            bg: [0.12, 0.14, 0.19],
+            fg: [0.8, 0.8, 0.78],
+            gray: [0.36, 0.4, 0.45],
+            special: [1.0, 0.9, 0.7],
+            border: [0.44, 0.48, 0.55],
+            life: [0.73, 0.9, 0.49],
+            life_medium: [1.0, 0.65, 0.35],
+            life_low: [1.0, 0.2, 0.2],
+            player: [1.0, 0.8, 0.4],
+            enemy: [0.95, 0.53, 0.47],
+            enemy_alt: [1.0, 0.84, 0.50],
+            white: [1.0, 1.0, 1.0],
+        }
+    }
 }
 
 impl fmt::Display for Theme {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            Theme::Dark => write!(f, "dark"),
-            Theme::Light => write!(f, "light"),
-        }
+        write!(f, "{}", self.name)
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Control {
     Normal,
     Advanced,
+    Mouse,
 }
 
 impl fmt::Display for Control {
@@ -214,16 +125,32 @@ impl fmt::Display for Control {
         match *self {
             Control::Normal => write!(f, "normal"),
             Control::Advanced => write!(f, "advanced"),
+            Control::Mouse => write!(f, "mouse"),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum GameStatus {
     GameOver,
     LevelCompleted,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase", tag = "kind", content = "chapter")]
+pub enum Mode {
+    Endless,
+    Story(usize),
+    Test,
+}
+
+/// Current UTC date as `YYYY-MM-DD`.
+pub fn today() -> String {
+    let mut date = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+    date.truncate(10);
+    date
+}
+
 pub fn normalize_angle(angle: f32) -> f32 {
     let max_arc_len = PI_2;
     if (angle).abs() >= max_arc_len {
@@ -239,6 +166,11 @@ pub fn get_level_radius(level: i32) -> f32 {
     FINAL_RADIUS + LEVEL_RADIUS * level as f32
 }
 
+/// Ring nearest to `radius`, the inverse of `get_level_radius`.
+pub fn get_level(radius: f32) -> i32 {
+    ((radius - FINAL_RADIUS) / LEVEL_RADIUS).round() as i32
+}
+
 pub fn approx_eq(a: f32, b: f32) -> bool {
     // it not best solution
     // read https://floating-point-gui.de/errors/comparison/
@@ -246,12 +178,17 @@ pub fn approx_eq(a: f32, b: f32) -> bool {
     (a - b).abs() < eps
 }
 
-pub fn create_map_of_element(level: usize) -> Vec<(f32, f32)> {
-    let mut rng = rand::thread_rng();
+pub fn create_map_of_element<R: Rng>(
+    rng: &mut R,
+    amount_element: usize,
+    density: f32,
+) -> Vec<(f32, f32)> {
     let magic_number = 2;
-    let amount_element = 8 + level;
+    let base_element = (amount_element as f32 * density) as usize;
 
-    let amount_full_element = rng.gen_range(amount_element / 2..amount_element / 2 + magic_number);
+    let amount_full_element = rng
+        .gen_range(base_element..base_element + magic_number)
+        .min(amount_element - 1);
     let mut map_of_element = vec![1; amount_full_element];
     let zero_vec = vec![0; amount_element - map_of_element.len()];
     map_of_element.extend_from_slice(&zero_vec);
@@ -260,7 +197,7 @@ pub fn create_map_of_element(level: usize) -> Vec<(f32, f32)> {
     let mut attempt = 0;
     'outer: while dubl && attempt < 5 {
         attempt += 1;
-        map_of_element.shuffle(&mut rng);
+        map_of_element.shuffle(rng);
         let mut el = 2;
         let mut c = 0;
         for x in &map_of_element {
@@ -291,10 +228,29 @@ pub fn create_map_of_element(level: usize) -> Vec<(f32, f32)> {
     result
 }
 
-#[allow(dead_code)]
-pub fn get_dir(keycode: Option<KeyCode>, angle: f32, horizontal_speed: f32) -> Option<Direction> {
-    match keycode {
-        Some(KeyCode::H) | Some(KeyCode::Left) | Some(KeyCode::Numpad4) => {
+/// Turn from `angle` toward `aim` along the shorter arc, by at most `max_speed`.
+pub fn aim_dir(aim: f32, angle: f32, max_speed: f32) -> Option<Direction> {
+    let mut diff = normalize_angle(aim - angle);
+    if diff > PI {
+        diff -= PI_2;
+    }
+    let speed = diff.abs().min(max_speed);
+    if speed < f32::EPSILON {
+        None
+    } else if diff > 0.0 {
+        Some(Direction::Left(speed))
+    } else {
+        Some(Direction::Right(speed))
+    }
+}
+
+pub fn get_dir(
+    action: Option<InputAction>,
+    angle: f32,
+    horizontal_speed: f32,
+) -> Option<Direction> {
+    match action {
+        Some(InputAction::MoveClockwise) => {
             // if angle >= PI * 0.25 && angle <= PI * 0.75 {
             //     Some(Direction::Left(horizontal_speed))
             // } else if angle >= PI * 0.75 && angle <= PI * 1.25 {
@@ -310,7 +266,7 @@ pub fn get_dir(keycode: Option<KeyCode>, angle: f32, horizontal_speed: f32) -> O
                 Some(Direction::Right(horizontal_speed))
             }
         }
-        Some(KeyCode::L) | Some(KeyCode::Right) | Some(KeyCode::Numpad6) => {
+        Some(InputAction::MoveCounterClockwise) => {
             // if angle >= PI * 0.25 && angle <= PI * 0.75 {
             //     Some(Direction::Right(horizontal_speed))
             // } else if angle >= PI * 0.75 && angle <= PI * 1.25 {
@@ -326,7 +282,7 @@ pub fn get_dir(keycode: Option<KeyCode>, angle: f32, horizontal_speed: f32) -> O
                 Some(Direction::Left(horizontal_speed))
             }
         }
-        Some(KeyCode::K) | Some(KeyCode::Down) | Some(KeyCode::Numpad2) => {
+        Some(InputAction::MoveOutward) => {
             // if angle >= PI * 0.25 && angle <= PI * 0.75 {
             //     Some(Direction::Down)
             // } else if angle >= PI * 0.75 && angle <= PI * 1.25 {
@@ -343,7 +299,7 @@ pub fn get_dir(keycode: Option<KeyCode>, angle: f32, horizontal_speed: f32) -> O
                 Some(Direction::Up)
             }
         }
-        Some(KeyCode::J) | Some(KeyCode::Up) | Some(KeyCode::Numpad8) => {
+        Some(InputAction::MoveInward) => {
             // if angle >= PI * 0.25 && angle <= PI * 0.75 {
             //     Some(Direction::Up)
             // } else if angle >= PI * 0.75 && angle <= PI * 1.25 {